@@ -1,59 +1,624 @@
-use crate::msg::InstantiateMsg;
-use crate::state::{Config, CONFIG, MEMBERS};
-use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult};
+use crate::distribution::ShareLimits;
+use crate::msg::{InstantiateMsg, JoinRequirement};
+use crate::state::{
+    ClosedEpoch, Config, OpenEpoch, CONFIG, DECAYED_WEIGHTS, DISTRIBUTION_CURSOR, EPOCHS,
+    EVENT_HOOKS, EXPIRES, HOOKS, JOINED, LEAVING, MEMBERS, MEMBER_COUNT, MEMBER_WEIGHTS,
+    OPEN_EPOCH, OWNERS, PAUSED, RESERVED, TOTAL_WEIGHT,
+};
+use cosmwasm_std::{
+    Addr, Coin, Decimal, Deps, DepsMut, Env, Fraction, MessageInfo, Order, Response, StdError,
+    StdResult, Storage, Timestamp,
+};
+use donation_peer::coins::{add_coins, sub_coins_saturating};
 
 pub const PEER_INSTANTIATE_ID: u64 = 1;
+pub const EVENT_HOOK_ID: u64 = 2;
+
+/// Gas available to every event hook notification. A hook running out of it fails on its own,
+/// without reverting the notifying action.
+pub const EVENT_HOOK_GAS_LIMIT: u64 = 500_000;
+
+pub const MAX_APPLICATION_METADATA_LEN: usize = 1024;
+
+pub const MAX_SPONSOR_NAME_LEN: usize = 64;
+
+pub const MAX_SPONSOR_MESSAGE_LEN: usize = 256;
+
+pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Response> {
+    if let Some(vesting) = &msg.vesting {
+        vesting.validate()?;
+    }
+
+    if let Some(governance) = &msg.governance {
+        governance.validate()?;
+    }
+
+    let pauser = msg
+        .pauser
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    let admin = msg
+        .admin
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    for share in [msg.max_share, msg.min_share].into_iter().flatten() {
+        if share.is_zero() || share > Decimal::one() {
+            return Err(StdError::generic_err(
+                "Share limits must be between zero and one",
+            ));
+        }
+    }
+
+    if let (Some(max_share), Some(min_share)) = (msg.max_share, msg.min_share) {
+        if min_share > max_share {
+            return Err(StdError::generic_err(
+                "Minimal share cannot exceed the maximal share",
+            ));
+        }
+    }
+
+    if msg.weight_half_life == Some(0) {
+        return Err(StdError::generic_err("Weight half-life must be non-zero"));
+    }
+
+    if msg.epoch_length == Some(0) {
+        return Err(StdError::generic_err("Epoch length must be non-zero"));
+    }
+
+    if let Some(term) = &msg.membership_term {
+        if term.duration == 0 {
+            return Err(StdError::generic_err(
+                "Membership term duration must be non-zero",
+            ));
+        }
+    }
+
+    if let Some(requirement) = &msg.join_requirement {
+        if requirement.amount().amount.is_zero() {
+            return Err(StdError::generic_err("Join requirement cannot be zero"));
+        }
+
+        if let JoinRequirement::Fee {
+            treasury: Some(treasury),
+            ..
+        } = requirement
+        {
+            deps.api.addr_validate(treasury)?;
+        }
+    }
 
-pub fn instantiate(deps: DepsMut, msg: InstantiateMsg) -> StdResult<Response> {
     let config = Config {
         peer_code_id: msg.peer_code_id,
         incremental_donation: msg.incremental_donation,
         collective_ratio: msg.collective_ratio,
+        vesting: msg.vesting,
+        pauser,
+        admin,
+        require_approval: msg.require_approval,
+        governance: msg.governance,
+        leave_notice_period: msg.leave_notice_period,
+        join_requirement: msg.join_requirement,
+        membership_term: msg.membership_term,
+        max_members: msg.max_members,
+        invite_only: msg.invite_only,
+        epoch_length: msg.epoch_length,
+        weight_half_life: msg.weight_half_life,
+        max_share: msg.max_share,
+        min_share: msg.min_share,
     };
     CONFIG.save(deps.storage, &config)?;
+    PAUSED.save(deps.storage, &false)?;
+    TOTAL_WEIGHT.save(deps.storage, &0)?;
+    MEMBER_COUNT.save(deps.storage, &0)?;
+    HOOKS.save(deps.storage, &vec![])?;
+    EVENT_HOOKS.save(deps.storage, &vec![])?;
+    RESERVED.save(deps.storage, &vec![])?;
+
+    if config.epoch_length.is_some() {
+        let epoch = OpenEpoch {
+            id: 0,
+            start: env.block.time,
+        };
+        OPEN_EPOCH.save(deps.storage, &epoch)?;
+        DISTRIBUTION_CURSOR.save(deps.storage, &0)?;
+    }
 
     Ok(Response::new())
 }
 
+/// Manager balance without the reserved funds - the part to be split between members
+fn distributable_funds(deps: Deps, env: &Env) -> StdResult<Vec<Coin>> {
+    let balance = deps
+        .querier
+        .query_all_balances(env.contract.address.clone())?;
+    let reserved = RESERVED.load(deps.storage)?;
+
+    Ok(sub_coins_saturating(&balance, &reserved))
+}
+
+fn reserve(storage: &mut dyn Storage, amount: &[Coin]) -> StdResult<()> {
+    RESERVED.update(storage, |mut reserved| -> StdResult<_> {
+        add_coins(&mut reserved, amount);
+        Ok(reserved)
+    })?;
+
+    Ok(())
+}
+
+fn release_reserved(storage: &mut dyn Storage, amount: &[Coin]) -> StdResult<()> {
+    RESERVED.update(storage, |reserved| -> StdResult<_> {
+        Ok(sub_coins_saturating(&reserved, amount))
+    })?;
+
+    Ok(())
+}
+
+/// The open epoch as it should be at `now` - if the open epoch ended, it is the one containing
+/// `now`
+fn current_epoch(epoch: &OpenEpoch, length: u64, now: Timestamp) -> OpenEpoch {
+    let passed = now.seconds().saturating_sub(epoch.start.seconds()) / length;
+    OpenEpoch {
+        id: epoch.id + passed,
+        start: epoch.start.plus_seconds(passed * length),
+    }
+}
+
+/// Closes the open epoch if it ended, snapshotting the weights at the current height and
+/// reserving the funds collected during the epoch. Funds which arrived with the current
+/// message - the `incoming` ones - belong to the next epoch.
+///
+/// Has to be called before any weight change, so the snapshot reflects weights from the
+/// epoch end.
+fn close_epoch(deps: DepsMut, env: &Env, incoming: &[Coin]) -> StdResult<()> {
+    let length = match CONFIG.load(deps.storage)?.epoch_length {
+        Some(length) => length,
+        None => return Ok(()),
+    };
+
+    let open = OPEN_EPOCH.load(deps.storage)?;
+    let current = current_epoch(&open, length, env.block.time);
+    if current.id == open.id {
+        return Ok(());
+    }
+
+    let funds = distributable_funds(deps.as_ref(), env)?;
+    let funds = sub_coins_saturating(&funds, incoming);
+
+    let epoch = ClosedEpoch {
+        start: open.start,
+        end: open.start.plus_seconds(length),
+        snapshot_height: env.block.height,
+        distributed: funds.is_empty(),
+        funds,
+    };
+    reserve(deps.storage, &epoch.funds)?;
+    EPOCHS.save(deps.storage, open.id, &epoch)?;
+    OPEN_EPOCH.save(deps.storage, &current)?;
+
+    Ok(())
+}
+
+/// Checks if the membership expired - at the snapshot time and height if `snapshot` is given,
+/// now otherwise
+fn is_expired(
+    deps: Deps,
+    env: &Env,
+    owner: &Addr,
+    snapshot: Option<(u64, Timestamp)>,
+) -> StdResult<bool> {
+    let (expires, now) = match snapshot {
+        Some((height, time)) => (
+            EXPIRES.may_load_at_height(deps.storage, owner, height)?,
+            time,
+        ),
+        None => (EXPIRES.may_load(deps.storage, owner)?, env.block.time),
+    };
+
+    Ok(expires.is_some_and(|expires| expires <= now))
+}
+
+/// Weights of the members taking part in distributions as `(peer, owner, weight)`. With
+/// `snapshot` given as `(height, time)`, weights snapshotted at the height are used, and members
+/// which joined later have no weight. Members expired as of the snapshot, or as of now without
+/// it, are skipped. With the weight half-life set, weights are decayed until the snapshot time,
+/// or until now without the snapshot.
+fn distribution_weights(
+    deps: Deps,
+    env: &Env,
+    snapshot: Option<(u64, Timestamp)>,
+) -> StdResult<Vec<(Addr, Addr, u128)>> {
+    let half_life = CONFIG.load(deps.storage)?.weight_half_life;
+
+    MEMBERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|member| -> Option<StdResult<_>> {
+            let (peer, owner) = match member {
+                Ok(member) => member,
+                Err(err) => return Some(Err(err)),
+            };
+
+            match is_expired(deps, env, &owner, snapshot) {
+                Ok(true) => None,
+                Ok(false) => {
+                    let weight = match half_life {
+                        Some(half_life) => decayed_weight(deps, env, &owner, snapshot, half_life),
+                        None => match snapshot {
+                            Some((height, _)) => MEMBER_WEIGHTS
+                                .may_load_at_height(deps.storage, &owner, height)
+                                .map(|weight| weight.unwrap_or_default() as u128),
+                            None => MEMBER_WEIGHTS
+                                .load(deps.storage, &owner)
+                                .map(|weight| weight as u128),
+                        },
+                    };
+                    Some(weight.map(|weight| (peer, owner, weight)))
+                }
+                Err(err) => Some(Err(err)),
+            }
+        })
+        .collect()
+}
+
+/// Decayed weight of the `owner` as atomics of the decayed score
+fn decayed_weight(
+    deps: Deps,
+    env: &Env,
+    owner: &Addr,
+    snapshot: Option<(u64, Timestamp)>,
+    half_life: u64,
+) -> StdResult<u128> {
+    let (weight, now) = match snapshot {
+        Some((height, time)) => (
+            DECAYED_WEIGHTS.may_load_at_height(deps.storage, owner, height)?,
+            time,
+        ),
+        None => (
+            DECAYED_WEIGHTS.may_load(deps.storage, owner)?,
+            env.block.time,
+        ),
+    };
+
+    Ok(weight
+        .map(|weight| weight.at(now, half_life).numerator().u128())
+        .unwrap_or_default())
+}
+
+/// Share limits of a single peer in distributions
+fn share_limits(deps: Deps) -> StdResult<ShareLimits> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ShareLimits {
+        min: config.min_share,
+        max: config.max_share,
+    })
+}
+
+/// Current weight of the `owner` in distributions
+fn current_weight(deps: Deps, env: &Env, owner: &Addr) -> StdResult<u128> {
+    match CONFIG.load(deps.storage)?.weight_half_life {
+        Some(half_life) => decayed_weight(deps, env, owner, None, half_life),
+        None => Ok(MEMBER_WEIGHTS
+            .may_load(deps.storage, owner)?
+            .unwrap_or_default() as u128),
+    }
+}
+
+/// Finds the peer owned by the `owner`
+fn member_peer(deps: Deps, owner: &Addr) -> StdResult<Option<Addr>> {
+    OWNERS.may_load(deps.storage, owner)
+}
+
 pub mod exec {
-    use cosmwasm_std::{to_binary, Addr, BankMsg, Order, SubMsg, SubMsgResult, Uint128, WasmMsg};
+    use cosmwasm_std::{
+        to_binary, BankMsg, Decimal, Event, Order, SubMsg, SubMsgResult, Uint128, WasmMsg,
+    };
+    use cw4::{MemberChangedHookMsg, MemberDiff};
+    use cw_storage_plus::Bound;
+    use cw_storage_plus::Item;
+    use cw_storage_plus::PrefixBound;
     use cw_utils::parse_instantiate_response_data;
-    use donation_peer::msg::InstantiateMsg as PeerInstantiate;
-    use donation_peer::state::STATE;
+    use donation_peer::msg::{
+        ExecMsg as PeerExec, InstantiateMsg as PeerInstantiate, MigrateMsg as PeerMigrate,
+    };
+    use donation_peer::state::{OWNER, STATE};
+    use sha2::{Digest, Sha256};
 
-    use crate::state::PENDING_INSTANTIATION;
+    use crate::msg::{
+        GovernanceConfig, PeerAmount, PoolHookExecuteMsg, PoolHookMsg, ProposalAction,
+        ProposalStatus, VoteOption, VotingWeight,
+    };
+    use crate::state::{
+        name_index_key, Application, Ballot, DecayedWeight, DistributionRecord, Invite,
+        MemberProfile, Proposal, Removal, Sponsorship, APPLICATIONS, APPROVED, DIRECTED,
+        DISTRIBUTIONS, DISTRIBUTION_COUNT, EXPIRING, INVITES, NAMES, NEXT_WAITLIST_TICKET,
+        PEER_EARNINGS, PROFILES, PROPOSALS, PROPOSAL_COUNT, RANKED, RANKS, REMOVED, SPONSORS,
+        SPONSOR_RANKS, STAKES, TAGGED, VOTES, WAITLIST, WAITLIST_TICKETS,
+    };
 
     use super::*;
 
-    pub fn join(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    use crate::distribution::split;
+
+    fn ensure_not_paused(deps: Deps) -> StdResult<()> {
+        if PAUSED.load(deps.storage)? {
+            return Err(StdError::generic_err("Contract is paused"));
+        }
+
+        Ok(())
+    }
+
+    pub fn join(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        invite: Option<String>,
+    ) -> StdResult<Response> {
+        ensure_not_paused(deps.as_ref())?;
+
         let sender = info.sender.to_string();
         let config = CONFIG.load(deps.storage)?;
 
+        if OWNERS.has(deps.storage, &info.sender) {
+            return Err(StdError::generic_err("Already a member"));
+        }
+
+        if WAITLIST_TICKETS.has(deps.storage, &info.sender) {
+            return Err(StdError::generic_err("Already on the waitlist"));
+        }
+
+        let banned = REMOVED
+            .may_load(deps.storage, &info.sender)?
+            .is_some_and(|removal| removal.banned);
+        if banned {
+            return Err(StdError::generic_err("Address is banned from the pool"));
+        }
+
+        if config.require_approval {
+            if !APPROVED.has(deps.storage, &info.sender) {
+                return Err(StdError::generic_err(
+                    "Joining requires an approved application",
+                ));
+            }
+
+            APPROVED.remove(deps.storage, &info.sender);
+        }
+
+        if config.invite_only {
+            let invite =
+                invite.ok_or_else(|| StdError::generic_err("Joining requires an invite"))?;
+            use_invite(deps.storage, &env, &invite)?;
+        }
+
+        let mut resp = Response::new();
+        if let Some(requirement) = &config.join_requirement {
+            let required = requirement.amount();
+            let paid = info
+                .funds
+                .iter()
+                .find(|coin| coin.denom == required.denom)
+                .map(|coin| coin.amount)
+                .unwrap_or_default();
+
+            if paid != required.amount || info.funds.len() != 1 {
+                return Err(StdError::generic_err(format!(
+                    "Joining requires exactly {}",
+                    required
+                )));
+            }
+
+            match requirement {
+                JoinRequirement::Fee {
+                    treasury: Some(treasury),
+                    ..
+                } => {
+                    resp = resp.add_message(BankMsg::Send {
+                        to_address: treasury.clone(),
+                        amount: vec![required.clone()],
+                    });
+                }
+                // Fee stays on the manager, and is distributed with the next donation
+                JoinRequirement::Fee { treasury: None, .. } => (),
+                JoinRequirement::Stake { .. } => {
+                    STAKES.save(deps.storage, &info.sender, required)?;
+                    reserve(deps.storage, std::slice::from_ref(required))?;
+                }
+            }
+        }
+
+        let resp = if is_full(deps.as_ref(), &config)? {
+            let ticket = NEXT_WAITLIST_TICKET
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            NEXT_WAITLIST_TICKET.save(deps.storage, &(ticket + 1))?;
+            WAITLIST.save(deps.storage, ticket, &info.sender)?;
+            WAITLIST_TICKETS.save(deps.storage, &info.sender, &ticket)?;
+
+            resp.add_attribute("waitlisted", "yes")
+        } else {
+            resp.add_submessage(admit(deps.as_ref(), &env, &info.sender)?)
+        };
+
+        let resp = resp
+            .add_attribute("action", "join")
+            .add_attribute("sender", sender);
+
+        Ok(resp)
+    }
+
+    /// Validates the invite code, and consumes a single use of it
+    fn use_invite(storage: &mut dyn Storage, env: &Env, code: &str) -> StdResult<()> {
+        let hash = hex::encode(Sha256::digest(code.as_bytes()));
+        let mut invite = INVITES
+            .may_load(storage, &hash)?
+            .ok_or_else(|| StdError::generic_err("Invalid invite"))?;
+
+        if invite
+            .expires
+            .is_some_and(|expires| expires <= env.block.time)
+        {
+            return Err(StdError::generic_err("Invite expired"));
+        }
+
+        match &mut invite.uses_left {
+            Some(1) => INVITES.remove(storage, &hash),
+            Some(uses_left) => {
+                *uses_left -= 1;
+                INVITES.save(storage, &hash, &invite)?;
+            }
+            None => (),
+        }
+
+        Ok(())
+    }
+
+    pub fn add_invite(
+        deps: DepsMut,
+        info: MessageInfo,
+        hash: String,
+        expires: Option<Timestamp>,
+        uses: Option<u32>,
+    ) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let hash = hash.to_lowercase();
+        let decoded = hex::decode(&hash)
+            .map_err(|err| StdError::generic_err(format!("Invalid invite hash: {}", err)))?;
+        if decoded.len() != 32 {
+            return Err(StdError::generic_err(
+                "Invite hash has to be a sha256 digest",
+            ));
+        }
+
+        if uses == Some(0) {
+            return Err(StdError::generic_err(
+                "Invite has to be usable at least once",
+            ));
+        }
+
+        if INVITES.has(deps.storage, &hash) {
+            return Err(StdError::generic_err("Invite already registered"));
+        }
+
+        let invite = Invite {
+            expires,
+            uses_left: uses,
+        };
+        INVITES.save(deps.storage, &hash, &invite)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "add_invite")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("hash", hash);
+
+        Ok(resp)
+    }
+
+    pub fn remove_invite(deps: DepsMut, info: MessageInfo, hash: String) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let hash = hash.to_lowercase();
+        if !INVITES.has(deps.storage, &hash) {
+            return Err(StdError::generic_err("No such invite"));
+        }
+
+        INVITES.remove(deps.storage, &hash);
+
+        let resp = Response::new()
+            .add_attribute("action", "remove_invite")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("hash", hash);
+
+        Ok(resp)
+    }
+
+    fn is_full(deps: Deps, config: &Config) -> StdResult<bool> {
+        let max_members = match config.max_members {
+            Some(max_members) => max_members,
+            None => return Ok(false),
+        };
+
+        Ok(MEMBER_COUNT.load(deps.storage)? >= max_members)
+    }
+
+    /// Instantiates the peer for the new member, which is registered when the instantiation
+    /// reply arrives
+    fn admit(deps: Deps, env: &Env, owner: &Addr) -> StdResult<SubMsg> {
+        let config = CONFIG.load(deps.storage)?;
+
         let msg = PeerInstantiate {
-            owner: sender.clone(),
+            owner: owner.to_string(),
             incremental_donation: config.incremental_donation,
             collective_ratio: config.collective_ratio,
+            vesting: config.vesting,
         };
 
+        // Manager is the peer admin, so it is able to upgrade it
         let msg = WasmMsg::Instantiate {
-            admin: None,
+            admin: Some(env.contract.address.to_string()),
             code_id: config.peer_code_id,
             msg: to_binary(&msg)?,
             funds: vec![],
-            label: format!("peer-{}", sender),
+            label: format!("peer-{}", owner),
         };
 
-        PENDING_INSTANTIATION.save(deps.storage, &info.sender)?;
+        Ok(SubMsg::reply_on_success(msg, PEER_INSTANTIATE_ID))
+    }
 
-        let resp = Response::new()
-            .add_submessage(SubMsg::reply_on_success(msg, PEER_INSTANTIATE_ID))
-            .add_attribute("action", "join")
-            .add_attribute("sender", sender);
+    /// Admits the first waitlisted address if there is a free slot in the pool
+    fn admit_waitlisted(deps: DepsMut, env: &Env) -> StdResult<Option<SubMsg>> {
+        let config = CONFIG.load(deps.storage)?;
+        if is_full(deps.as_ref(), &config)? {
+            return Ok(None);
+        }
+
+        let next = WAITLIST
+            .range(deps.storage, None, None, Order::Ascending)
+            .next()
+            .transpose()?;
+
+        let (ticket, owner) = match next {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+
+        WAITLIST.remove(deps.storage, ticket);
+        WAITLIST_TICKETS.remove(deps.storage, &owner);
+
+        admit(deps.as_ref(), env, &owner).map(Some)
+    }
+
+    pub fn leave_waitlist(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+        let ticket = WAITLIST_TICKETS
+            .may_load(deps.storage, &info.sender)?
+            .ok_or_else(|| StdError::generic_err("Not on the waitlist"))?;
+
+        WAITLIST.remove(deps.storage, ticket);
+        WAITLIST_TICKETS.remove(deps.storage, &info.sender);
+
+        let mut resp = Response::new();
+        if let Some(stake) = STAKES.may_load(deps.storage, &info.sender)? {
+            STAKES.remove(deps.storage, &info.sender);
+            release_reserved(deps.storage, std::slice::from_ref(&stake))?;
+
+            resp = resp.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![stake],
+            });
+        }
+
+        let resp = resp
+            .add_attribute("action", "leave_waitlist")
+            .add_attribute("sender", info.sender.to_string());
 
         Ok(resp)
     }
 
-    pub fn peer_instantiate_reply(deps: DepsMut, msg: SubMsgResult) -> StdResult<Response> {
+    pub fn peer_instantiate_reply(
+        deps: DepsMut,
+        env: Env,
+        msg: SubMsgResult,
+    ) -> StdResult<Response> {
         let resp = match msg.into_result() {
             Ok(resp) => resp,
             Err(err) => return Err(StdError::generic_err(err)),
@@ -65,121 +630,1820 @@ pub mod exec {
         let resp = parse_instantiate_response_data(&data)
             .map_err(|err| StdError::generic_err(err.to_string()))?;
 
-        let addr = PENDING_INSTANTIATION.load(deps.storage)?;
+        // Multiple peers might be instantiated in a single transaction, so the owner is taken
+        // from the peer itself
         let peer = Addr::unchecked(&resp.contract_address);
+        let owner = OWNER.query(&deps.querier, peer.clone())?;
+
+        MEMBERS.save(deps.storage, peer.clone(), &owner)?;
+        MEMBER_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+        OWNERS.save(deps.storage, &owner, &peer)?;
+        JOINED.save(deps.storage, &owner, &env.block.time)?;
+
+        if let Some(term) = CONFIG.load(deps.storage)?.membership_term {
+            let expires = env.block.time.plus_seconds(term.duration);
+            EXPIRES.save(deps.storage, &owner, &expires, env.block.height)?;
+            EXPIRING.save(deps.storage, (expires.nanos(), &owner), &())?;
+        }
 
-        MEMBERS.save(deps.storage, peer, &addr)?;
+        let event = PoolHookMsg::MemberJoined {
+            member: owner.clone(),
+            peer,
+        };
+        let events = event_hook_msgs(deps.as_ref(), event)?;
+        let hooks = set_member_weight(deps, &env, &owner, Some(0))?;
 
-        Ok(Response::new())
+        Ok(Response::new().add_messages(hooks).add_submessages(events))
     }
 
-    pub fn leave(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
-        let member = MEMBERS
-            .range(deps.storage, None, None, Order::Ascending)
-            .filter_map(|addr| addr.ok())
-            .find(|(_, addr)| *addr == info.sender);
+    /// With the notice period configured, the first call only announces leaving, and the member
+    /// calls `Leave {}` again once the period is over.
+    pub fn leave(mut deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+        // Leaving settles pool funds and admits waitlisted addresses
+        ensure_not_paused(deps.as_ref())?;
 
-        if let Some((peer, _)) = member {
-            MEMBERS.remove(deps.storage, peer);
-        } else {
-            return Err(StdError::generic_err(
-                "No such member or state read failure",
-            ));
+        let peer = member_peer(deps.as_ref(), &info.sender)?
+            .ok_or_else(|| StdError::generic_err("No such member"))?;
+
+        let config = CONFIG.load(deps.storage)?;
+        match (
+            LEAVING.may_load(deps.storage, &info.sender)?,
+            config.leave_notice_period,
+        ) {
+            (None, Some(notice_period)) => {
+                let leave_at = env.block.time.plus_seconds(notice_period);
+                LEAVING.save(deps.storage, &info.sender, &leave_at)?;
+
+                let resp = Response::new()
+                    .add_attribute("action", "leave")
+                    .add_attribute("sender", info.sender.to_string())
+                    .add_attribute("leave_at", leave_at.to_string());
+
+                return Ok(resp);
+            }
+            (Some(leave_at), _) if env.block.time < leave_at => {
+                return Err(StdError::generic_err(format!(
+                    "Notice period ends at {}",
+                    leave_at
+                )));
+            }
+            _ => (),
         }
 
-        Ok(Response::new())
+        let settlement = settle(deps.branch(), &env, &info.sender, &peer)?;
+        let resp = remove_member(deps, &env, peer, true)?;
+
+        Ok(settlement
+            .add_submessages(resp.messages)
+            .add_attributes(resp.attributes)
+            .add_attribute("action", "leave")
+            .add_attribute("sender", info.sender.to_string()))
     }
 
-    pub fn donate(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-        let weights: Vec<_> = MEMBERS
-            .keys(deps.storage, None, None, Order::Ascending)
-            .map(|peer| -> StdResult<_> {
-                let peer = peer?;
-                let donators = STATE.query(&deps.querier, peer.clone())?.donators;
-                Ok((peer, donators as u128))
-            })
-            .collect::<StdResult<_>>()?;
+    /// Share of the `owner` in `funds` split with weights at `snapshot`
+    fn member_share(
+        deps: Deps,
+        env: &Env,
+        owner: &Addr,
+        funds: &[Coin],
+        snapshot: Option<(u64, Timestamp)>,
+    ) -> StdResult<Vec<Coin>> {
+        let weights: Vec<_> = distribution_weights(deps, env, snapshot)?
+            .into_iter()
+            .map(|(_, member, weight)| (member, weight))
+            .collect();
+        let share = split(funds, &weights, &share_limits(deps)?)
+            .into_iter()
+            .find(|amount| amount.peer == *owner)
+            .map(|amount| amount.amount)
+            .unwrap_or_default();
 
-        let total: u128 = weights.iter().map(|(_, weight)| weight).sum();
+        Ok(share)
+    }
 
-        let funds = deps.querier.query_all_balances(env.contract.address)?;
-        let send_msgs = weights.into_iter().filter_map(|(peer, weights)| {
-            let coins: Vec<_> = funds
-                .iter()
-                .cloned()
-                .map(|mut coin| {
-                    coin.amount = Uint128::new(coin.amount.u128() * weights / total);
-                    coin
-                })
-                .collect();
+    /// Sends the member peer its share of the funds waiting on the manager. With epochs, closed
+    /// epochs have to be distributed before, and the open one is left to the remaining members.
+    fn settle(mut deps: DepsMut, env: &Env, owner: &Addr, peer: &Addr) -> StdResult<Response> {
+        close_epoch(deps.branch(), env, &[])?;
 
-            if coins.iter().all(|c| c.amount == Uint128::zero()) {
-                None
-            } else {
-                Some(BankMsg::Send {
-                    to_address: peer.to_string(),
-                    amount: coins,
-                })
+        let amount = if CONFIG.load(deps.storage)?.epoch_length.is_some() {
+            // Settling every pending epoch here could take more gas than a single transaction
+            // has, so the member share arrives with the regular distribution
+            let cursor = DISTRIBUTION_CURSOR.load(deps.storage)?;
+            let pending = EPOCHS
+                .keys(
+                    deps.storage,
+                    Some(Bound::inclusive(cursor)),
+                    None,
+                    Order::Ascending,
+                )
+                .next()
+                .is_some();
+            if pending {
+                return Err(StdError::generic_err(
+                    "Closed epochs have to be distributed with `DistributeEpochs {}` first",
+                ));
             }
-        });
+            vec![]
+        } else {
+            let funds = distributable_funds(deps.as_ref(), env)?;
+            member_share(deps.as_ref(), env, owner, &funds, None)?
+        };
+
+        if amount.is_empty() {
+            return Ok(Response::new());
+        }
+
+        let event = Event::new("settlement")
+            .add_attribute("member", owner.to_string())
+            .add_attribute("peer", peer.to_string())
+            .add_attribute(
+                "amount",
+                amount
+                    .iter()
+                    .map(Coin::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+
+        record_earnings(deps.storage, peer, &amount)?;
+
+        let hook_event = PoolHookMsg::Settlement {
+            member: owner.clone(),
+            peer: peer.clone(),
+            amount: amount.clone(),
+        };
+        let events = event_hook_msgs(deps.as_ref(), hook_event)?;
 
         let resp = Response::new()
-            .add_messages(send_msgs)
-            .add_attribute("action", "donate")
-            .add_attribute("sender", info.sender.to_string());
+            .add_message(BankMsg::Send {
+                to_address: peer.to_string(),
+                amount,
+            })
+            .add_submessages(events)
+            .add_event(event);
 
         Ok(resp)
     }
-}
 
-pub mod query {
-    use cw_storage_plus::Bound;
+    /// Removes the member owning the `peer` - common path for all the ways of leaving the pool.
+    /// The freed slot is taken by the first waitlisted address. Without `refund_stake` the
+    /// stake is slashed entirely.
+    fn remove_member(
+        mut deps: DepsMut,
+        env: &Env,
+        peer: Addr,
+        refund_stake: bool,
+    ) -> StdResult<Response> {
+        let owner = MEMBERS.load(deps.storage, peer.clone())?;
+        MEMBERS.remove(deps.storage, peer.clone());
+        MEMBER_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count - 1) })?;
+        OWNERS.remove(deps.storage, &owner);
+        LEAVING.remove(deps.storage, &owner);
+        if let Some(expires) = EXPIRES.may_load(deps.storage, &owner)? {
+            EXPIRES.remove(deps.storage, &owner, env.block.height)?;
+            EXPIRING.remove(deps.storage, (expires.nanos(), &owner));
+        }
+        JOINED.remove(deps.storage, &owner);
+        set_profile(deps.storage, &owner, None)?;
 
-    use super::*;
+        // Stake left after slashing is returned to the owner, unless the member is expelled. Then
+        // it is no longer reserved, and it is distributed with the next donation.
+        let mut resp = Response::new();
+        if let Some(stake) = STAKES.may_load(deps.storage, &owner)? {
+            STAKES.remove(deps.storage, &owner);
+            release_reserved(deps.storage, std::slice::from_ref(&stake))?;
 
-    use crate::msg::{ConfigResp, Member, MemberPeerAddrResp, MembersListResp};
+            resp = if refund_stake {
+                resp.add_message(BankMsg::Send {
+                    to_address: owner.to_string(),
+                    amount: vec![stake],
+                })
+            } else {
+                resp.add_attribute("slashed", stake.to_string())
+            };
+        }
 
-    pub fn config(deps: Deps) -> StdResult<ConfigResp> {
-        let config = CONFIG.load(deps.storage)?;
-        Ok(ConfigResp {
-            incremental_donation: config.incremental_donation,
-            collective_ratio: config.collective_ratio,
-        })
-    }
+        let event = PoolHookMsg::MemberLeft {
+            member: owner.clone(),
+            peer: peer.clone(),
+        };
+        let events = event_hook_msgs(deps.as_ref(), event)?;
+        let hooks = set_member_weight(deps.branch(), env, &owner, None)?;
 
-    pub fn member_peer_addr(deps: Deps, addr: &str) -> StdResult<MemberPeerAddrResp> {
-        let peer = MEMBERS
-            .range(deps.storage, None, None, Order::Ascending)
-            .filter_map(|addr| addr.ok())
-            .find(|(_, member)| member.as_str() == addr);
+        let detach_msg = WasmMsg::Execute {
+            contract_addr: peer.to_string(),
+            msg: to_binary(&PeerExec::Detach {})?,
+            funds: vec![],
+        };
 
-        let (peer, _) = peer.ok_or_else(|| StdError::generic_err("No such member"))?;
+        let mut resp = resp
+            .add_message(detach_msg)
+            .add_messages(hooks)
+            .add_submessages(events)
+            .add_attribute("removed_peer", peer.to_string());
 
-        Ok(MemberPeerAddrResp { addr: peer })
+        if let Some(admission) = admit_waitlisted(deps, env)? {
+            resp = resp.add_submessage(admission);
+        }
+
+        Ok(resp)
     }
 
-    pub fn members_list(
-        deps: Deps,
-        start_after: Option<String>,
-        limit: Option<u64>,
-    ) -> StdResult<MembersListResp> {
-        let start_after = start_after
-            .map(|addr| deps.api.addr_validate(&addr))
-            .transpose()?;
+    pub fn renew(mut deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+        // The ended epoch has to see the expiration from before the renewal
+        close_epoch(deps.branch(), &env, &info.funds)?;
 
-        let members = MEMBERS
-            .range(
-                deps.storage,
-                start_after.map(Bound::exclusive),
+        let term = CONFIG
+            .load(deps.storage)?
+            .membership_term
+            .ok_or_else(|| StdError::generic_err("Membership does not expire"))?;
+
+        let expires = EXPIRES
+            .may_load(deps.storage, &info.sender)?
+            .ok_or_else(|| StdError::generic_err("No such member"))?;
+
+        if let Some(fee) = &term.renewal_fee {
+            // Fee stays on the manager, and is distributed with the next donation
+            if info.funds.len() != 1 || info.funds[0] != *fee {
+                return Err(StdError::generic_err(format!(
+                    "Renewal requires exactly {}",
+                    fee
+                )));
+            }
+        }
+
+        // Renewing expired, but not yet pruned membership starts the new term now
+        EXPIRING.remove(deps.storage, (expires.nanos(), &info.sender));
+        let expires = expires.max(env.block.time).plus_seconds(term.duration);
+        EXPIRES.save(deps.storage, &info.sender, &expires, env.block.height)?;
+        EXPIRING.save(deps.storage, (expires.nanos(), &info.sender), &())?;
+
+        let resp = Response::new()
+            .add_attribute("action", "renew")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("expires", expires.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn prune(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        limit: Option<u32>,
+    ) -> StdResult<Response> {
+        const DEFAULT_LIMIT: u32 = 10;
+        const MAX_LIMIT: u32 = 30;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let expired: Vec<_> = EXPIRING
+            .prefix_range(
+                deps.storage,
+                None,
+                Some(PrefixBound::inclusive(env.block.time.nanos())),
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|expiry| expiry.map(|((_, owner), _)| owner))
+            .collect::<StdResult<_>>()?;
+
+        let mut resp = Response::new();
+        for owner in &expired {
+            let peer = OWNERS.load(deps.storage, owner)?;
+            let removal = remove_member(deps.branch(), &env, peer, true)?;
+            resp = resp
+                .add_submessages(removal.messages)
+                .add_attributes(removal.attributes);
+        }
+
+        let resp = resp
+            .add_attribute("action", "prune")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("pruned", expired.len().to_string());
+
+        Ok(resp)
+    }
+
+    pub fn slash(
+        deps: DepsMut,
+        info: MessageInfo,
+        owner: String,
+        amount: Option<Uint128>,
+    ) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let owner = deps.api.addr_validate(&owner)?;
+        let mut stake = STAKES
+            .may_load(deps.storage, &owner)?
+            .ok_or_else(|| StdError::generic_err("No stake to slash"))?;
+
+        let amount = amount.unwrap_or(stake.amount);
+        if amount.is_zero() || amount > stake.amount {
+            return Err(StdError::generic_err(format!(
+                "Cannot slash {}{}, stake is {}",
+                amount, stake.denom, stake
+            )));
+        }
+
+        // Slashed funds are no longer reserved, so they are distributed with the next donation
+        let slashed = Coin {
+            denom: stake.denom.clone(),
+            amount,
+        };
+        release_reserved(deps.storage, std::slice::from_ref(&slashed))?;
+
+        stake.amount -= amount;
+        if stake.amount.is_zero() {
+            STAKES.remove(deps.storage, &owner);
+        } else {
+            STAKES.save(deps.storage, &owner, &stake)?;
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "slash")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("owner", owner.to_string())
+            .add_attribute("slashed", slashed.to_string());
+
+        Ok(resp)
+    }
+
+    /// Forced removal of the member, banning it from rejoining
+    fn expel_member(deps: DepsMut, env: &Env, owner: &Addr, reason: String) -> StdResult<Response> {
+        let peer = member_peer(deps.as_ref(), owner)?
+            .ok_or_else(|| StdError::generic_err("No such member"))?;
+
+        let removal = Removal {
+            peer: peer.clone(),
+            reason: reason.clone(),
+            removed_at: env.block.time,
+            banned: true,
+        };
+        REMOVED.save(deps.storage, owner, &removal)?;
+
+        let resp = remove_member(deps, env, peer, false)?.add_attribute("reason", reason);
+        Ok(resp)
+    }
+
+    pub fn remove_member_by_admin(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        owner: String,
+        reason: String,
+    ) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let owner = deps.api.addr_validate(&owner)?;
+        let resp = expel_member(deps, &env, &owner, reason)?
+            .add_attribute("action", "remove_member")
+            .add_attribute("sender", info.sender.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn unban(deps: DepsMut, info: MessageInfo, owner: String) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let owner = deps.api.addr_validate(&owner)?;
+        REMOVED.update(deps.storage, &owner, |removal| -> StdResult<_> {
+            let mut removal = removal
+                .filter(|removal| removal.banned)
+                .ok_or_else(|| StdError::generic_err("Address is not banned"))?;
+            removal.banned = false;
+            Ok(removal)
+        })?;
+
+        let resp = Response::new()
+            .add_attribute("action", "unban")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("owner", owner.to_string());
+
+        Ok(resp)
+    }
+
+    /// Prepares the `event` notifications for all registered event hooks. Failing hooks do not
+    /// revert the notifying action.
+    fn event_hook_msgs(deps: Deps, event: PoolHookMsg) -> StdResult<Vec<SubMsg>> {
+        let msg = to_binary(&PoolHookExecuteMsg::PoolHook(event))?;
+
+        let hooks = EVENT_HOOKS
+            .load(deps.storage)?
+            .into_iter()
+            .map(|hook| {
+                let msg = WasmMsg::Execute {
+                    contract_addr: hook.to_string(),
+                    msg: msg.clone(),
+                    funds: vec![],
+                };
+                SubMsg::reply_on_error(msg, EVENT_HOOK_ID).with_gas_limit(EVENT_HOOK_GAS_LIMIT)
+            })
+            .collect();
+
+        Ok(hooks)
+    }
+
+    pub fn event_hook_reply(msg: SubMsgResult) -> StdResult<Response> {
+        let mut resp = Response::new();
+        if let SubMsgResult::Err(err) = msg {
+            resp = resp.add_attribute("event_hook_error", err);
+        }
+
+        Ok(resp)
+    }
+
+    /// Updates the member weight keeping the total weight in sync. Returns cw4 hooks
+    /// notifications if the weight changed.
+    fn set_member_weight(
+        mut deps: DepsMut,
+        env: &Env,
+        owner: &Addr,
+        weight: Option<u64>,
+    ) -> StdResult<Vec<WasmMsg>> {
+        close_epoch(deps.branch(), env, &[])?;
+
+        let old = MEMBER_WEIGHTS.may_load(deps.storage, owner)?;
+        if old == weight {
+            return Ok(vec![]);
+        }
+
+        match weight {
+            Some(weight) => MEMBER_WEIGHTS.save(deps.storage, owner, &weight, env.block.height)?,
+            None => MEMBER_WEIGHTS.remove(deps.storage, owner, env.block.height)?,
+        }
+
+        let rank = match (CONFIG.load(deps.storage)?.weight_half_life, weight) {
+            (Some(half_life), Some(weight)) => {
+                let mut decayed = DECAYED_WEIGHTS
+                    .may_load(deps.storage, owner)?
+                    .unwrap_or_else(|| DecayedWeight::new(env.block.time));
+                decayed.update(old.unwrap_or_default(), weight, env.block.time, half_life);
+                DECAYED_WEIGHTS.save(deps.storage, owner, &decayed, env.block.height)?;
+                Some(decayed.rank(half_life))
+            }
+            (Some(_), None) => {
+                DECAYED_WEIGHTS.remove(deps.storage, owner, env.block.height)?;
+                None
+            }
+            (None, weight) => weight.map(u128::from),
+        };
+        set_member_rank(deps.storage, owner, rank)?;
+
+        let total = TOTAL_WEIGHT.load(deps.storage)?;
+        let total = total + weight.unwrap_or_default() - old.unwrap_or_default();
+        TOTAL_WEIGHT.save(deps.storage, &total)?;
+
+        let diff = MemberDiff::new(owner, old, weight);
+        let msg = MemberChangedHookMsg::one(diff).into_binary()?;
+
+        let hooks = HOOKS
+            .load(deps.storage)?
+            .into_iter()
+            .map(|hook| WasmMsg::Execute {
+                contract_addr: hook.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            })
+            .collect();
+
+        Ok(hooks)
+    }
+
+    pub fn update_profile(
+        deps: DepsMut,
+        info: MessageInfo,
+        name: String,
+        tags: Vec<String>,
+    ) -> StdResult<Response> {
+        let owner = MEMBERS
+            .may_load(deps.storage, info.sender.clone())?
+            .ok_or_else(|| StdError::generic_err("Unauthorized"))?;
+
+        set_profile(deps.storage, &owner, Some(MemberProfile { name, tags }))?;
+
+        let resp = Response::new()
+            .add_attribute("action", "update_profile")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("owner", owner.to_string());
+
+        Ok(resp)
+    }
+
+    /// Updates the member profile together with the tag and name indexes, removing it with no
+    /// `profile`
+    fn set_profile(
+        storage: &mut dyn Storage,
+        owner: &Addr,
+        profile: Option<MemberProfile>,
+    ) -> StdResult<()> {
+        if let Some(old) = PROFILES.may_load(storage, owner)? {
+            for tag in &old.tags {
+                TAGGED.remove(storage, (tag, owner));
+            }
+            NAMES.remove(storage, &name_index_key(&old.name, owner));
+        }
+
+        match profile {
+            Some(profile) => {
+                for tag in &profile.tags {
+                    TAGGED.save(storage, (tag, owner), &())?;
+                }
+                NAMES.save(storage, &name_index_key(&profile.name, owner), &())?;
+                PROFILES.save(storage, owner, &profile)
+            }
+            None => {
+                PROFILES.remove(storage, owner);
+                Ok(())
+            }
+        }
+    }
+
+    /// Moves the member in the ranking, removing it with no `rank`
+    fn set_member_rank(
+        storage: &mut dyn Storage,
+        owner: &Addr,
+        rank: Option<u128>,
+    ) -> StdResult<()> {
+        if let Some(old) = RANKS.may_load(storage, owner)? {
+            RANKED.remove(storage, (old, owner));
+        }
+
+        match rank {
+            Some(rank) => {
+                RANKS.save(storage, owner, &rank)?;
+                RANKED.save(storage, (rank, owner), &())
+            }
+            None => {
+                RANKS.remove(storage, owner);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn donate(mut deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+        ensure_not_paused(deps.as_ref())?;
+
+        if let Some(directed) = DIRECTED.may_load(deps.storage, &info.sender)? {
+            DIRECTED.remove(deps.storage, &info.sender);
+            release_reserved(deps.storage, &directed)?;
+        }
+
+        close_epoch(deps.branch(), &env, &info.funds)?;
+
+        // Donation forwarded by the member peer, its donators count might have changed
+        let mut hooks = vec![];
+        if let Some(owner) = MEMBERS.may_load(deps.storage, info.sender.clone())? {
+            let donators = STATE.query(&deps.querier, info.sender.clone())?.donators;
+            hooks = set_member_weight(deps.branch(), &env, &owner, Some(donators))?;
+        }
+
+        let distribution = distribute_donation(deps, &env, &info.sender)?;
+
+        let resp = Response::new()
+            .add_messages(hooks)
+            .add_attribute("action", "donate")
+            .add_attribute("sender", info.sender.to_string())
+            .add_submessages(distribution.messages)
+            .add_attributes(distribution.attributes);
+
+        Ok(resp)
+    }
+
+    /// Distributes funds waiting on the manager after a donation arrived
+    fn distribute_donation(deps: DepsMut, env: &Env, sender: &Addr) -> StdResult<Response> {
+        // With epochs, donations wait for the epoch to be distributed
+        if CONFIG.load(deps.storage)?.epoch_length.is_some() {
+            return Ok(Response::new());
+        }
+
+        // Without any donators funds cannot be split - they wait on the manager for the next
+        // donation
+        let funds = distributable_funds(deps.as_ref(), env)?;
+        distribute(deps, env, sender, &funds, None)
+    }
+
+    pub fn sponsor(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        name: String,
+        message: String,
+    ) -> StdResult<Response> {
+        ensure_not_paused(deps.as_ref())?;
+
+        if info.funds.is_empty() {
+            return Err(StdError::generic_err("No funds to sponsor"));
+        }
+
+        if name.is_empty() || name.len() > MAX_SPONSOR_NAME_LEN {
+            return Err(StdError::generic_err(format!(
+                "Sponsor name must be between 1 and {} bytes",
+                MAX_SPONSOR_NAME_LEN
+            )));
+        }
+
+        if message.len() > MAX_SPONSOR_MESSAGE_LEN {
+            return Err(StdError::generic_err(format!(
+                "Sponsor message cannot exceed {} bytes",
+                MAX_SPONSOR_MESSAGE_LEN
+            )));
+        }
+
+        close_epoch(deps.branch(), &env, &info.funds)?;
+
+        let denom = CONFIG.load(deps.storage)?.incremental_donation.denom;
+        let mut sponsorship = match SPONSORS.may_load(deps.storage, &info.sender)? {
+            Some(sponsorship) => {
+                SPONSOR_RANKS.remove(deps.storage, (sponsorship.rank, &info.sender));
+                sponsorship
+            }
+            None => Sponsorship {
+                name: String::new(),
+                message: String::new(),
+                total: vec![],
+                rank: 0,
+            },
+        };
+
+        sponsorship.name = name;
+        sponsorship.message = message;
+        add_coins(&mut sponsorship.total, &info.funds);
+        sponsorship.rank = sponsorship.total_in(&denom);
+        SPONSORS.save(deps.storage, &info.sender, &sponsorship)?;
+        SPONSOR_RANKS.save(deps.storage, (sponsorship.rank, &info.sender), &())?;
+
+        let distribution = distribute_donation(deps, &env, &info.sender)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "sponsor")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("name", sponsorship.name)
+            .add_submessages(distribution.messages)
+            .add_attributes(distribution.attributes);
+
+        Ok(resp)
+    }
+
+    pub fn donate_to(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        peers: Vec<(String, Decimal)>,
+        count_as_donator: bool,
+    ) -> StdResult<Response> {
+        ensure_not_paused(deps.as_ref())?;
+
+        if info.funds.is_empty() {
+            return Err(StdError::generic_err("No funds to donate"));
+        }
+
+        let mut targets: Vec<(Addr, Addr, Decimal)> = vec![];
+        for (peer, fraction) in peers {
+            let peer = deps.api.addr_validate(&peer)?;
+            let owner = MEMBERS
+                .may_load(deps.storage, peer.clone())?
+                .ok_or_else(|| StdError::generic_err(format!("Not a member peer: {}", peer)))?;
+
+            if fraction.is_zero() {
+                return Err(StdError::generic_err("Fractions must be non-zero"));
+            }
+
+            if targets.iter().any(|(target, _, _)| *target == peer) {
+                return Err(StdError::generic_err(format!("Duplicated peer: {}", peer)));
+            }
+
+            targets.push((peer, owner, fraction));
+        }
+
+        let total = targets
+            .iter()
+            .fold(Decimal::zero(), |total, (_, _, fraction)| total + *fraction);
+        if total != Decimal::one() {
+            return Err(StdError::generic_err("Fractions must sum up to one"));
+        }
+
+        // Directed funds never wait on the manager, so they are not part of any epoch
+        close_epoch(deps.branch(), &env, &info.funds)?;
+
+        // The last peer takes the rounding dust
+        let mut left = info.funds.clone();
+        let mut amounts = vec![];
+        for (idx, (peer, _, fraction)) in targets.iter().enumerate() {
+            let amount = if idx + 1 == targets.len() {
+                left.clone()
+            } else {
+                let amount: Vec<_> = info
+                    .funds
+                    .iter()
+                    .map(|coin| Coin {
+                        denom: coin.denom.clone(),
+                        amount: coin.amount * *fraction,
+                    })
+                    .filter(|coin| !coin.amount.is_zero())
+                    .collect();
+                left = sub_coins_saturating(&left, &amount);
+                amount
+            };
+
+            amounts.push(PeerAmount {
+                peer: peer.clone(),
+                amount,
+            });
+        }
+        amounts.retain(|amount| !amount.amount.is_empty());
+
+        // The collective part of counted donations is recorded once it is distributed, so only
+        // the part kept by the peers belongs to this distribution
+        let recorded: Vec<_> = if count_as_donator {
+            let ratio = CONFIG.load(deps.storage)?.collective_ratio;
+            amounts
+                .iter()
+                .map(|amount| {
+                    let kept = amount
+                        .amount
+                        .iter()
+                        .map(|coin| Coin {
+                            denom: coin.denom.clone(),
+                            amount: coin.amount - coin.amount * ratio,
+                        })
+                        .filter(|coin| !coin.amount.is_zero())
+                        .collect();
+                    PeerAmount {
+                        peer: amount.peer.clone(),
+                        amount: kept,
+                    }
+                })
+                .filter(|amount| !amount.amount.is_empty())
+                .collect()
+        } else {
+            amounts.clone()
+        };
+
+        let id = record_distribution(
+            deps.storage,
+            &env,
+            &info.sender,
+            &recorded,
+            Uint128::zero(),
+            true,
+        )?;
+
+        let mut resp = Response::new();
+        for amount in &amounts {
+            // Counted donations go through the peer like any other, so the donator weight
+            // always costs the collective part. Funds for the peers later in the queue must
+            // not be distributed when the collective part comes back.
+            resp = if count_as_donator {
+                reserve(deps.storage, &amount.amount)?;
+                DIRECTED.save(deps.storage, &amount.peer, &amount.amount)?;
+                resp.add_message(WasmMsg::Execute {
+                    contract_addr: amount.peer.to_string(),
+                    msg: to_binary(&PeerExec::Donate {})?,
+                    funds: amount.amount.clone(),
+                })
+            } else {
+                resp.add_message(BankMsg::Send {
+                    to_address: amount.peer.to_string(),
+                    amount: amount.amount.clone(),
+                })
+            };
+        }
+
+        let event = PoolHookMsg::Distribution {
+            sender: info.sender.clone(),
+            amounts: recorded,
+        };
+        let events = event_hook_msgs(deps.as_ref(), event)?;
+
+        let resp = resp
+            .add_submessages(events)
+            .add_attribute("action", "donate_to")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("distribution_id", id.to_string());
+
+        Ok(resp)
+    }
+
+    /// Sends `funds` to the member peers, split with weights at `snapshot`
+    fn distribute(
+        deps: DepsMut,
+        env: &Env,
+        sender: &Addr,
+        funds: &[Coin],
+        snapshot: Option<(u64, Timestamp)>,
+    ) -> StdResult<Response> {
+        let weights: Vec<_> = distribution_weights(deps.as_ref(), env, snapshot)?
+            .into_iter()
+            .map(|(peer, _, weight)| (peer, weight))
+            .collect();
+        let amounts = split(funds, &weights, &share_limits(deps.as_ref())?);
+        if amounts.is_empty() {
+            return Ok(Response::new());
+        }
+
+        let send_msgs: Vec<_> = amounts
+            .iter()
+            .map(|amount| BankMsg::Send {
+                to_address: amount.peer.to_string(),
+                amount: amount.amount.clone(),
+            })
+            .collect();
+
+        let total_weight = weights.iter().map(|(_, weight)| weight).sum::<u128>();
+        let id = record_distribution(
+            deps.storage,
+            env,
+            sender,
+            &amounts,
+            Uint128::new(total_weight),
+            false,
+        )?;
+
+        let event = PoolHookMsg::Distribution {
+            sender: sender.clone(),
+            amounts,
+        };
+        let events = event_hook_msgs(deps.as_ref(), event)?;
+
+        Ok(Response::new()
+            .add_messages(send_msgs)
+            .add_submessages(events)
+            .add_attribute("distribution_id", id.to_string()))
+    }
+
+    /// Stores the distribution in the history and adds it to the peer earnings, returns its id
+    fn record_distribution(
+        storage: &mut dyn Storage,
+        env: &Env,
+        sender: &Addr,
+        amounts: &[PeerAmount],
+        total_weight: Uint128,
+        directed: bool,
+    ) -> StdResult<u64> {
+        for amount in amounts {
+            record_earnings(storage, &amount.peer, &amount.amount)?;
+        }
+
+        let id = DISTRIBUTION_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+        DISTRIBUTION_COUNT.save(storage, &id)?;
+
+        let record = DistributionRecord {
+            height: env.block.height,
+            time: env.block.time,
+            sender: sender.clone(),
+            amounts: amounts.to_vec(),
+            total_weight,
+            directed,
+        };
+        DISTRIBUTIONS.save(storage, id, &record)?;
+
+        Ok(id)
+    }
+
+    fn record_earnings(storage: &mut dyn Storage, peer: &Addr, amount: &[Coin]) -> StdResult<()> {
+        let mut earned = PEER_EARNINGS.may_load(storage, peer)?.unwrap_or_default();
+        add_coins(&mut earned, amount);
+        PEER_EARNINGS.save(storage, peer, &earned)
+    }
+
+    pub fn distribute_epochs(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        limit: Option<u32>,
+    ) -> StdResult<Response> {
+        ensure_not_paused(deps.as_ref())?;
+
+        const DEFAULT_LIMIT: u32 = 5;
+        const MAX_LIMIT: u32 = 20;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        if CONFIG.load(deps.storage)?.epoch_length.is_none() {
+            return Err(StdError::generic_err("Epochs are not enabled"));
+        }
+
+        close_epoch(deps.branch(), &env, &[])?;
+
+        let cursor = DISTRIBUTION_CURSOR.load(deps.storage)?;
+        let epochs: Vec<_> = EPOCHS
+            .range(
+                deps.storage,
+                Some(Bound::inclusive(cursor)),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+
+        let count = epochs.len();
+        let mut resp = Response::new();
+        for (id, mut epoch) in epochs {
+            // Funds which cannot be split, like without any weight, roll over to the open
+            // epoch
+            release_reserved(deps.storage, &epoch.funds)?;
+
+            if !epoch.distributed {
+                let distribution = distribute(
+                    deps.branch(),
+                    &env,
+                    &info.sender,
+                    &epoch.funds,
+                    Some((epoch.snapshot_height, epoch.end)),
+                )?;
+                resp = resp.add_submessages(distribution.messages);
+
+                epoch.distributed = true;
+                EPOCHS.save(deps.storage, id, &epoch)?;
+            }
+
+            DISTRIBUTION_CURSOR.save(deps.storage, &(id + 1))?;
+        }
+
+        let resp = resp
+            .add_attribute("action", "distribute_epochs")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("epochs", count.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn apply(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        metadata: String,
+    ) -> StdResult<Response> {
+        ensure_not_paused(deps.as_ref())?;
+
+        let config = CONFIG.load(deps.storage)?;
+        if !config.require_approval {
+            return Err(StdError::generic_err(
+                "Applications are disabled, join directly",
+            ));
+        }
+
+        if member_peer(deps.as_ref(), &info.sender)?.is_some() {
+            return Err(StdError::generic_err("Already a member"));
+        }
+
+        if APPLICATIONS.has(deps.storage, &info.sender) || APPROVED.has(deps.storage, &info.sender)
+        {
+            return Err(StdError::generic_err("Application already submitted"));
+        }
+
+        if metadata.len() > MAX_APPLICATION_METADATA_LEN {
+            return Err(StdError::generic_err(format!(
+                "Application metadata cannot exceed {} bytes",
+                MAX_APPLICATION_METADATA_LEN
+            )));
+        }
+
+        let application = Application {
+            metadata,
+            applied_at: env.block.time,
+        };
+        APPLICATIONS.save(deps.storage, &info.sender, &application)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "apply")
+            .add_attribute("sender", info.sender.to_string());
+
+        Ok(resp)
+    }
+
+    /// Applications can be reviewed by the admin and by any existing member
+    fn ensure_reviewer(deps: Deps, sender: &Addr) -> StdResult<()> {
+        let config = CONFIG.load(deps.storage)?;
+        if config.admin.as_ref() == Some(sender) || member_peer(deps, sender)?.is_some() {
+            Ok(())
+        } else {
+            Err(StdError::generic_err("Unauthorized"))
+        }
+    }
+
+    pub fn approve(deps: DepsMut, info: MessageInfo, applicant: String) -> StdResult<Response> {
+        ensure_reviewer(deps.as_ref(), &info.sender)?;
+
+        let applicant = deps.api.addr_validate(&applicant)?;
+        if !APPLICATIONS.has(deps.storage, &applicant) {
+            return Err(StdError::generic_err("No such application"));
+        }
+
+        APPLICATIONS.remove(deps.storage, &applicant);
+        APPROVED.save(deps.storage, &applicant, &info.sender)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "approve")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("applicant", applicant.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn reject(
+        deps: DepsMut,
+        info: MessageInfo,
+        applicant: String,
+        reason: Option<String>,
+    ) -> StdResult<Response> {
+        ensure_reviewer(deps.as_ref(), &info.sender)?;
+
+        let applicant = deps.api.addr_validate(&applicant)?;
+        if !APPLICATIONS.has(deps.storage, &applicant) {
+            return Err(StdError::generic_err("No such application"));
+        }
+
+        APPLICATIONS.remove(deps.storage, &applicant);
+
+        let resp = Response::new()
+            .add_attribute("action", "reject")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("applicant", applicant.to_string())
+            .add_attribute("reason", reason.unwrap_or_default());
+
+        Ok(resp)
+    }
+
+    fn ensure_admin(deps: Deps, sender: &Addr) -> StdResult<()> {
+        let config = CONFIG.load(deps.storage)?;
+        if config.admin.as_ref() != Some(sender) {
+            return Err(StdError::generic_err("Unauthorized"));
+        }
+
+        Ok(())
+    }
+
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        collective_ratio: Option<Decimal>,
+        incremental_donation: Option<Coin>,
+    ) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let resp = apply_config_update(deps, collective_ratio, incremental_donation)?
+            .add_attribute("action", "update_config")
+            .add_attribute("sender", info.sender.to_string());
+
+        Ok(resp)
+    }
+
+    /// Updates the configuration of the manager and all the peers
+    fn apply_config_update(
+        deps: DepsMut,
+        collective_ratio: Option<Decimal>,
+        incremental_donation: Option<Coin>,
+    ) -> StdResult<Response> {
+        if matches!(collective_ratio, Some(ratio) if ratio > Decimal::one()) {
+            return Err(StdError::generic_err("Collective ratio cannot exceed 1"));
+        }
+
+        let mut config = CONFIG.load(deps.storage)?;
+        if let Some(collective_ratio) = collective_ratio {
+            config.collective_ratio = collective_ratio;
+        }
+        if let Some(incremental_donation) = &incremental_donation {
+            config.incremental_donation = incremental_donation.clone();
+        }
+        CONFIG.save(deps.storage, &config)?;
+
+        let msg = to_binary(&PeerExec::UpdateConfig {
+            incremental_donation,
+            collective_ratio,
+        })?;
+
+        let update_msgs = MEMBERS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|peer| -> StdResult<_> {
+                Ok(WasmMsg::Execute {
+                    contract_addr: peer?.to_string(),
+                    msg: msg.clone(),
+                    funds: vec![],
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(Response::new().add_messages(update_msgs))
+    }
+
+    pub fn upgrade_peers(deps: DepsMut, info: MessageInfo, code_id: u64) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let resp = apply_peers_upgrade(deps, code_id)?
+            .add_attribute("action", "upgrade_peers")
+            .add_attribute("sender", info.sender.to_string());
+
+        Ok(resp)
+    }
+
+    /// Switches new peers to the `code_id` and migrates all existing ones
+    fn apply_peers_upgrade(deps: DepsMut, code_id: u64) -> StdResult<Response> {
+        let mut config = CONFIG.load(deps.storage)?;
+        config.peer_code_id = code_id;
+        CONFIG.save(deps.storage, &config)?;
+
+        let msg = to_binary(&PeerMigrate {})?;
+        let migrate_msgs = MEMBERS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|peer| -> StdResult<_> {
+                Ok(WasmMsg::Migrate {
+                    contract_addr: peer?.to_string(),
+                    new_code_id: code_id,
+                    msg: msg.clone(),
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let resp = Response::new()
+            .add_messages(migrate_msgs)
+            .add_attribute("code_id", code_id.to_string());
+
+        Ok(resp)
+    }
+
+    fn governance_config(deps: Deps) -> StdResult<GovernanceConfig> {
+        CONFIG
+            .load(deps.storage)?
+            .governance
+            .ok_or_else(|| StdError::generic_err("Governance is disabled"))
+    }
+
+    /// Voting weight of the `owner` at the `height`, if it was a member then
+    fn voting_weight(
+        deps: Deps,
+        governance: &GovernanceConfig,
+        owner: &Addr,
+        height: u64,
+    ) -> StdResult<Option<u64>> {
+        let donators = MEMBER_WEIGHTS.may_load_at_height(deps.storage, owner, height)?;
+        let weight = match governance.voting_weight {
+            VotingWeight::Member => donators.map(|_| 1),
+            VotingWeight::Donators => donators,
+        };
+
+        Ok(weight)
+    }
+
+    pub fn propose(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        title: String,
+        description: String,
+        action: ProposalAction,
+    ) -> StdResult<Response> {
+        let governance = governance_config(deps.as_ref())?;
+
+        if member_peer(deps.as_ref(), &info.sender)?.is_none() {
+            return Err(StdError::generic_err("Only members can create proposals"));
+        }
+
+        match &action {
+            ProposalAction::UpdateConfig {
+                collective_ratio: Some(ratio),
+                ..
+            } if *ratio > Decimal::one() => {
+                return Err(StdError::generic_err("Collective ratio cannot exceed 1"))
+            }
+            ProposalAction::RemoveMember { owner, .. } => {
+                let owner = deps.api.addr_validate(owner)?;
+                if member_peer(deps.as_ref(), &owner)?.is_none() {
+                    return Err(StdError::generic_err("No such member"));
+                }
+            }
+            _ => (),
+        }
+
+        let id = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        PROPOSAL_COUNT.save(deps.storage, &id)?;
+
+        let total_weight = match governance.voting_weight {
+            VotingWeight::Member => MEMBER_COUNT.load(deps.storage)? as u64,
+            VotingWeight::Donators => TOTAL_WEIGHT.load(deps.storage)?,
+        };
+
+        let proposal = Proposal {
+            proposer: info.sender.clone(),
+            title,
+            description,
+            action,
+            expires: env.block.time.plus_seconds(governance.voting_period),
+            start_height: env.block.height,
+            total_weight,
+            yes: 0,
+            no: 0,
+            abstain: 0,
+            executed: false,
+        };
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "propose")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("proposal_id", id.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        proposal_id: u64,
+        vote: VoteOption,
+    ) -> StdResult<Response> {
+        let governance = governance_config(deps.as_ref())?;
+
+        if member_peer(deps.as_ref(), &info.sender)?.is_none() {
+            return Err(StdError::generic_err("Only members can vote"));
+        }
+
+        let mut proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+        if proposal.status(&governance, env.block.time) != ProposalStatus::Open {
+            return Err(StdError::generic_err("Proposal is not open for voting"));
+        }
+
+        if VOTES.has(deps.storage, (proposal_id, &info.sender)) {
+            return Err(StdError::generic_err("Already voted"));
+        }
+
+        // Weights are taken from the proposal creation, so neither joining nor gaining
+        // donators later changes the outcome
+        let weight = voting_weight(
+            deps.as_ref(),
+            &governance,
+            &info.sender,
+            proposal.start_height,
+        )?
+        .ok_or_else(|| StdError::generic_err("Only members at the proposal creation can vote"))?;
+        match vote {
+            VoteOption::Yes => proposal.yes += weight,
+            VoteOption::No => proposal.no += weight,
+            VoteOption::Abstain => proposal.abstain += weight,
+        }
+
+        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+        VOTES.save(
+            deps.storage,
+            (proposal_id, &info.sender),
+            &Ballot { vote, weight },
+        )?;
+
+        let resp = Response::new()
+            .add_attribute("action", "vote")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("proposal_id", proposal_id.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn execute_proposal(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        proposal_id: u64,
+    ) -> StdResult<Response> {
+        let governance = governance_config(deps.as_ref())?;
+
+        let mut proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+        if proposal.status(&governance, env.block.time) != ProposalStatus::Passed {
+            return Err(StdError::generic_err("Proposal did not pass"));
+        }
+
+        proposal.executed = true;
+        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+
+        let resp = match proposal.action {
+            ProposalAction::UpdateConfig {
+                collective_ratio,
+                incremental_donation,
+            } => apply_config_update(deps, collective_ratio, incremental_donation)?,
+            ProposalAction::RemoveMember { owner, reason } => {
+                let owner = deps.api.addr_validate(&owner)?;
+                expel_member(deps, &env, &owner, reason)?
+            }
+            ProposalAction::UpgradePeers { code_id } => apply_peers_upgrade(deps, code_id)?,
+        };
+
+        let resp = resp
+            .add_attribute("action", "execute_proposal")
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("proposal_id", proposal_id.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn add_hook(deps: DepsMut, info: MessageInfo, addr: String) -> StdResult<Response> {
+        register_hook(deps, info, &HOOKS, addr, "add_hook")
+    }
+
+    pub fn remove_hook(deps: DepsMut, info: MessageInfo, addr: String) -> StdResult<Response> {
+        unregister_hook(deps, info, &HOOKS, addr, "remove_hook")
+    }
+
+    pub fn add_event_hook(deps: DepsMut, info: MessageInfo, addr: String) -> StdResult<Response> {
+        register_hook(deps, info, &EVENT_HOOKS, addr, "add_event_hook")
+    }
+
+    pub fn remove_event_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        addr: String,
+    ) -> StdResult<Response> {
+        unregister_hook(deps, info, &EVENT_HOOKS, addr, "remove_event_hook")
+    }
+
+    fn register_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        hooks_list: &Item<Vec<Addr>>,
+        addr: String,
+        action: &str,
+    ) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let addr = deps.api.addr_validate(&addr)?;
+        let mut hooks = hooks_list.load(deps.storage)?;
+        if hooks.contains(&addr) {
+            return Err(StdError::generic_err("Hook already registered"));
+        }
+
+        hooks.push(addr.clone());
+        hooks_list.save(deps.storage, &hooks)?;
+
+        let resp = Response::new()
+            .add_attribute("action", action)
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("hook", addr.to_string());
+
+        Ok(resp)
+    }
+
+    fn unregister_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        hooks_list: &Item<Vec<Addr>>,
+        addr: String,
+        action: &str,
+    ) -> StdResult<Response> {
+        ensure_admin(deps.as_ref(), &info.sender)?;
+
+        let addr = deps.api.addr_validate(&addr)?;
+        let mut hooks = hooks_list.load(deps.storage)?;
+        if !hooks.contains(&addr) {
+            return Err(StdError::generic_err("No such hook"));
+        }
+
+        hooks.retain(|hook| *hook != addr);
+        hooks_list.save(deps.storage, &hooks)?;
+
+        let resp = Response::new()
+            .add_attribute("action", action)
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("hook", addr.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> StdResult<Response> {
+        let config = CONFIG.load(deps.storage)?;
+        if config.pauser.as_ref() != Some(&info.sender) {
+            return Err(StdError::generic_err("Unauthorized"));
+        }
+
+        PAUSED.save(deps.storage, &paused)?;
+
+        let resp = Response::new()
+            .add_attribute("action", if paused { "pause" } else { "unpause" })
+            .add_attribute("sender", info.sender.to_string());
+
+        Ok(resp)
+    }
+}
+
+pub mod query {
+    use cosmwasm_std::Uint128;
+    use cw_storage_plus::Bound;
+
+    use super::*;
+
+    use crate::distribution::{fractions, split};
+
+    use crate::msg::{
+        ApplicationResp, ApplicationsResp, ClosedEpochResp, ConfigResp, DistributionResp,
+        DistributionsResp, EpochHistoryResp, EpochResp, ExpectedShareResp, InviteResp, InvitesResp,
+        Member, MemberPeerAddrResp, MemberStats, MembersListResp, MembersStatsResp, PausedResp,
+        PeerEarningsResp, PendingLeaveResp, ProposalResp, ProposalsResp, RankedPeer,
+        RemovedMemberResp, RemovedMembersResp, SimulateDistributionResp, SponsorResp, SponsorsResp,
+        StakeResp, TopPeersResp, VoteInfo, VoteResp, VotesResp, WaitlistPositionResp, WaitlistResp,
+    };
+    use crate::state::{
+        name_index_owner, DistributionRecord, Proposal, APPLICATIONS, DISTRIBUTIONS, INVITES,
+        MEMBER_WEIGHTS, NAMES, PEER_EARNINGS, PROFILES, PROPOSALS, RANKED, RANKS, REMOVED,
+        SPONSORS, SPONSOR_RANKS, STAKES, TAGGED, VOTES, WAITLIST, WAITLIST_TICKETS,
+    };
+    use cw4::{
+        AdminResponse, HooksResponse, Member as GroupMember, MemberListResponse, MemberResponse,
+        TotalWeightResponse,
+    };
+
+    // Pagination limits of the cw4 queries
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    pub fn config(deps: Deps) -> StdResult<ConfigResp> {
+        let config = CONFIG.load(deps.storage)?;
+        Ok(ConfigResp {
+            incremental_donation: config.incremental_donation,
+            collective_ratio: config.collective_ratio,
+            vesting: config.vesting,
+            admin: config.admin,
+            require_approval: config.require_approval,
+            governance: config.governance,
+            leave_notice_period: config.leave_notice_period,
+            join_requirement: config.join_requirement,
+            membership_term: config.membership_term,
+            max_members: config.max_members,
+            invite_only: config.invite_only,
+            epoch_length: config.epoch_length,
+            weight_half_life: config.weight_half_life,
+            max_share: config.max_share,
+            min_share: config.min_share,
+        })
+    }
+
+    pub fn member_peer_addr(deps: Deps, addr: &str) -> StdResult<MemberPeerAddrResp> {
+        let addr = deps.api.addr_validate(addr)?;
+        let peer =
+            member_peer(deps, &addr)?.ok_or_else(|| StdError::generic_err("No such member"))?;
+
+        Ok(MemberPeerAddrResp { addr: peer })
+    }
+
+    pub fn members_list(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u64>,
+    ) -> StdResult<MembersListResp> {
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let members = MEMBERS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|member| -> StdResult<_> {
+                let (peer, addr) = member?;
+                member_info(deps, peer, addr)
+            });
+
+        let members: Vec<_> = if let Some(limit) = limit {
+            members.take(limit as usize).collect::<StdResult<_>>()
+        } else {
+            members.collect()
+        }?;
+
+        Ok(MembersListResp { members })
+    }
+
+    fn member_info(deps: Deps, peer: Addr, addr: Addr) -> StdResult<Member> {
+        let expires = EXPIRES.may_load(deps.storage, &addr)?;
+        let (name, tags) = match PROFILES.may_load(deps.storage, &addr)? {
+            Some(profile) => (Some(profile.name), profile.tags),
+            None => (None, vec![]),
+        };
+
+        Ok(Member {
+            addr,
+            peer_addr: peer,
+            expires,
+            name,
+            tags,
+        })
+    }
+
+    pub fn members_by_tag(
+        deps: Deps,
+        tag: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<MembersListResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let members = TAGGED
+            .prefix(&tag)
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|owner| -> StdResult<_> {
+                let owner = owner?;
+                let peer = OWNERS.load(deps.storage, &owner)?;
+                member_info(deps, peer, owner)
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(MembersListResp { members })
+    }
+
+    pub fn members_by_name_prefix(
+        deps: Deps,
+        prefix: String,
+        limit: Option<u32>,
+    ) -> StdResult<MembersListResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let prefix = prefix.to_lowercase();
+
+        let members = NAMES
+            .keys(
+                deps.storage,
+                Some(Bound::inclusive(prefix.as_str())),
+                None,
+                Order::Ascending,
+            )
+            .take_while(|key| {
+                key.as_ref()
+                    .map_or(true, |key| key.starts_with(prefix.as_str()))
+            })
+            .take(limit)
+            .map(|key| -> StdResult<_> {
+                let owner = name_index_owner(&key?)
+                    .ok_or_else(|| StdError::generic_err("Invalid name index key"))?;
+                let peer = OWNERS.load(deps.storage, &owner)?;
+                member_info(deps, peer, owner)
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(MembersListResp { members })
+    }
+
+    pub fn paused(deps: Deps) -> StdResult<PausedResp> {
+        let config = CONFIG.load(deps.storage)?;
+        Ok(PausedResp {
+            paused: PAUSED.load(deps.storage)?,
+            pauser: config.pauser,
+        })
+    }
+
+    pub fn applications(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u64>,
+    ) -> StdResult<ApplicationsResp> {
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let applications = APPLICATIONS
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|application| -> StdResult<_> {
+                let (applicant, application) = application?;
+
+                Ok(ApplicationResp {
+                    applicant,
+                    metadata: application.metadata,
+                    applied_at: application.applied_at,
+                })
+            });
+
+        let applications: Vec<_> = if let Some(limit) = limit {
+            applications.take(limit as usize).collect::<StdResult<_>>()
+        } else {
+            applications.collect()
+        }?;
+
+        Ok(ApplicationsResp { applications })
+    }
+
+    fn proposal_resp(
+        deps: Deps,
+        env: &Env,
+        id: u64,
+        proposal: Proposal,
+    ) -> StdResult<ProposalResp> {
+        let governance = CONFIG
+            .load(deps.storage)?
+            .governance
+            .ok_or_else(|| StdError::generic_err("Governance is disabled"))?;
+
+        Ok(ProposalResp {
+            id,
+            status: proposal.status(&governance, env.block.time),
+            proposer: proposal.proposer,
+            title: proposal.title,
+            description: proposal.description,
+            action: proposal.action,
+            expires: proposal.expires,
+            total_weight: proposal.total_weight,
+            yes: proposal.yes,
+            no: proposal.no,
+            abstain: proposal.abstain,
+        })
+    }
+
+    pub fn proposal(deps: Deps, env: Env, proposal_id: u64) -> StdResult<ProposalResp> {
+        let proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+        proposal_resp(deps, &env, proposal_id, proposal)
+    }
+
+    pub fn proposals(
+        deps: Deps,
+        env: Env,
+        start_after: Option<u64>,
+        limit: Option<u64>,
+    ) -> StdResult<ProposalsResp> {
+        let proposals = PROPOSALS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|proposal| -> StdResult<_> {
+                let (id, proposal) = proposal?;
+                proposal_resp(deps, &env, id, proposal)
+            });
+
+        let proposals: Vec<_> = if let Some(limit) = limit {
+            proposals.take(limit as usize).collect::<StdResult<_>>()
+        } else {
+            proposals.collect()
+        }?;
+
+        Ok(ProposalsResp { proposals })
+    }
+
+    pub fn vote(deps: Deps, proposal_id: u64, voter: String) -> StdResult<VoteResp> {
+        let voter = deps.api.addr_validate(&voter)?;
+        let vote = VOTES
+            .may_load(deps.storage, (proposal_id, &voter))?
+            .map(|ballot| VoteInfo {
+                voter,
+                vote: ballot.vote,
+                weight: ballot.weight,
+            });
+
+        Ok(VoteResp { vote })
+    }
+
+    pub fn votes(
+        deps: Deps,
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u64>,
+    ) -> StdResult<VotesResp> {
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let votes = VOTES
+            .prefix(proposal_id)
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|vote| -> StdResult<_> {
+                let (voter, ballot) = vote?;
+
+                Ok(VoteInfo {
+                    voter,
+                    vote: ballot.vote,
+                    weight: ballot.weight,
+                })
+            });
+
+        let votes: Vec<_> = if let Some(limit) = limit {
+            votes.take(limit as usize).collect::<StdResult<_>>()
+        } else {
+            votes.collect()
+        }?;
+
+        Ok(VotesResp { votes })
+    }
+
+    pub fn admin(deps: Deps) -> StdResult<AdminResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        Ok(AdminResponse {
+            admin: config.admin.map(|admin| admin.to_string()),
+        })
+    }
+
+    pub fn total_weight(deps: Deps) -> StdResult<TotalWeightResponse> {
+        Ok(TotalWeightResponse {
+            weight: TOTAL_WEIGHT.load(deps.storage)?,
+        })
+    }
+
+    pub fn list_members(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<MemberListResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let members = MEMBER_WEIGHTS
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
                 None,
                 Order::Ascending,
             )
+            .take(limit)
             .map(|member| -> StdResult<_> {
-                let (peer, addr) = member?;
+                let (addr, weight) = member?;
+                Ok(GroupMember {
+                    addr: addr.to_string(),
+                    weight,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(MemberListResponse { members })
+    }
+
+    pub fn member(deps: Deps, addr: String, at_height: Option<u64>) -> StdResult<MemberResponse> {
+        let addr = deps.api.addr_validate(&addr)?;
+        let weight = match at_height {
+            Some(height) => MEMBER_WEIGHTS.may_load_at_height(deps.storage, &addr, height)?,
+            None => MEMBER_WEIGHTS.may_load(deps.storage, &addr)?,
+        };
+
+        Ok(MemberResponse { weight })
+    }
+
+    pub fn hooks(deps: Deps) -> StdResult<HooksResponse> {
+        let hooks = HOOKS
+            .load(deps.storage)?
+            .into_iter()
+            .map(|hook| hook.to_string())
+            .collect();
+
+        Ok(HooksResponse { hooks })
+    }
+
+    pub fn event_hooks(deps: Deps) -> StdResult<HooksResponse> {
+        let hooks = EVENT_HOOKS
+            .load(deps.storage)?
+            .into_iter()
+            .map(|hook| hook.to_string())
+            .collect();
+
+        Ok(HooksResponse { hooks })
+    }
+
+    pub fn pending_leave(deps: Deps, addr: &str) -> StdResult<PendingLeaveResp> {
+        let addr = deps.api.addr_validate(addr)?;
+        let leave_at = LEAVING.may_load(deps.storage, &addr)?;
+
+        Ok(PendingLeaveResp { leave_at })
+    }
+
+    pub fn removed_members(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u64>,
+    ) -> StdResult<RemovedMembersResp> {
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let members = REMOVED
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|removal| -> StdResult<_> {
+                let (addr, removal) = removal?;
 
-                Ok(Member {
+                Ok(RemovedMemberResp {
                     addr,
-                    peer_addr: peer,
+                    peer: removal.peer,
+                    reason: removal.reason,
+                    removed_at: removal.removed_at,
+                    banned: removal.banned,
                 })
             });
 
@@ -189,6 +2453,365 @@ pub mod query {
             members.collect()
         }?;
 
-        Ok(MembersListResp { members })
+        Ok(RemovedMembersResp { members })
+    }
+
+    pub fn stake(deps: Deps, addr: &str) -> StdResult<StakeResp> {
+        let addr = deps.api.addr_validate(addr)?;
+        let stake = STAKES.may_load(deps.storage, &addr)?;
+
+        Ok(StakeResp { stake })
+    }
+
+    pub fn waitlist(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<WaitlistResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let start_after = start_after
+            .map(|addr| -> StdResult<_> {
+                let addr = deps.api.addr_validate(&addr)?;
+                WAITLIST_TICKETS.load(deps.storage, &addr)
+            })
+            .transpose()?;
+
+        let waitlist = WAITLIST
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|entry| entry.map(|(_, addr)| addr))
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+
+        Ok(WaitlistResp { waitlist })
+    }
+
+    pub fn waitlist_position(deps: Deps, addr: &str) -> StdResult<WaitlistPositionResp> {
+        let addr = deps.api.addr_validate(addr)?;
+
+        let position = WAITLIST_TICKETS
+            .may_load(deps.storage, &addr)?
+            .map(|ticket| {
+                WAITLIST
+                    .keys(
+                        deps.storage,
+                        None,
+                        Some(Bound::inclusive(ticket)),
+                        Order::Ascending,
+                    )
+                    .count() as u64
+            });
+
+        Ok(WaitlistPositionResp { position })
+    }
+
+    pub fn invites(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<InvitesResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after.map(|hash| hash.to_lowercase());
+
+        let invites = INVITES
+            .range(
+                deps.storage,
+                start_after.as_deref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|invite| -> StdResult<_> {
+                let (hash, invite) = invite?;
+
+                Ok(InviteResp {
+                    hash,
+                    expires: invite.expires,
+                    uses_left: invite.uses_left,
+                })
+            })
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+
+        Ok(InvitesResp { invites })
+    }
+
+    pub fn epoch(deps: Deps, env: Env) -> StdResult<EpochResp> {
+        let length = CONFIG
+            .load(deps.storage)?
+            .epoch_length
+            .ok_or_else(|| StdError::generic_err("Epochs are not enabled"))?;
+
+        let open = OPEN_EPOCH.load(deps.storage)?;
+        let current = current_epoch(&open, length, env.block.time);
+        let funds = distributable_funds(deps, &env)?;
+
+        Ok(EpochResp {
+            id: current.id,
+            start: current.start,
+            end: current.start.plus_seconds(length),
+            funds,
+        })
+    }
+
+    pub fn epoch_history(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<EpochHistoryResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let epochs = EPOCHS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|epoch| -> StdResult<_> {
+                let (id, epoch) = epoch?;
+
+                Ok(ClosedEpochResp {
+                    id,
+                    start: epoch.start,
+                    end: epoch.end,
+                    snapshot_height: epoch.snapshot_height,
+                    funds: epoch.funds,
+                    distributed: epoch.distributed,
+                })
+            })
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+
+        Ok(EpochHistoryResp { epochs })
+    }
+
+    /// Splits `funds` the same way a donation would with the current weights. With epochs the
+    /// actual split uses weights snapshotted at the epoch end.
+    pub fn simulate_distribution(
+        deps: Deps,
+        env: Env,
+        funds: Vec<Coin>,
+    ) -> StdResult<SimulateDistributionResp> {
+        let weights: Vec<_> = distribution_weights(deps, &env, None)?
+            .into_iter()
+            .map(|(peer, _, weight)| (peer, weight))
+            .collect();
+        let amounts = split(&funds, &weights, &share_limits(deps)?);
+
+        let mut distributed = vec![];
+        for amount in &amounts {
+            add_coins(&mut distributed, &amount.amount);
+        }
+        let leftover = sub_coins_saturating(&funds, &distributed);
+
+        Ok(SimulateDistributionResp { amounts, leftover })
+    }
+
+    pub fn expected_share(deps: Deps, env: Env, peer: String) -> StdResult<ExpectedShareResp> {
+        let peer = deps.api.addr_validate(&peer)?;
+        if !MEMBERS.has(deps.storage, peer.clone()) {
+            return Err(StdError::generic_err("No such member"));
+        }
+
+        let weights: Vec<_> = distribution_weights(deps, &env, None)?
+            .into_iter()
+            .map(|(peer, _, weight)| (peer, weight))
+            .collect();
+        let share = fractions(&weights, &share_limits(deps)?)
+            .into_iter()
+            .find(|(member, _)| *member == peer)
+            .map(|(_, share)| share)
+            .unwrap_or_default();
+
+        Ok(ExpectedShareResp { share })
+    }
+
+    pub fn distributions(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<DistributionsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let distributions = DISTRIBUTIONS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .map(|distribution| -> StdResult<_> {
+                let (id, distribution) = distribution?;
+                Ok(distribution_resp(id, distribution))
+            })
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+
+        Ok(DistributionsResp { distributions })
+    }
+
+    pub fn distribution(deps: Deps, id: u64) -> StdResult<DistributionResp> {
+        let distribution = DISTRIBUTIONS
+            .may_load(deps.storage, id)?
+            .ok_or_else(|| StdError::generic_err("No such distribution"))?;
+
+        Ok(distribution_resp(id, distribution))
+    }
+
+    fn distribution_resp(id: u64, distribution: DistributionRecord) -> DistributionResp {
+        DistributionResp {
+            id,
+            height: distribution.height,
+            time: distribution.time,
+            sender: distribution.sender,
+            amounts: distribution.amounts,
+            total_weight: distribution.total_weight,
+            directed: distribution.directed,
+        }
+    }
+
+    pub fn peer_earnings(deps: Deps, peer: &str) -> StdResult<PeerEarningsResp> {
+        let peer = deps.api.addr_validate(peer)?;
+        let earned = PEER_EARNINGS
+            .may_load(deps.storage, &peer)?
+            .unwrap_or_default();
+
+        Ok(PeerEarningsResp { earned })
+    }
+
+    pub fn members_stats(
+        deps: Deps,
+        env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<MembersStatsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let members = MEMBERS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|member| -> StdResult<_> {
+                let (peer, addr) = member?;
+
+                Ok(MemberStats {
+                    donators: MEMBER_WEIGHTS
+                        .may_load(deps.storage, &addr)?
+                        .unwrap_or_default(),
+                    weight: Uint128::new(current_weight(deps, &env, &addr)?),
+                    balance: deps.querier.query_all_balances(&peer)?,
+                    earned: PEER_EARNINGS
+                        .may_load(deps.storage, &peer)?
+                        .unwrap_or_default(),
+                    joined_at: JOINED.may_load(deps.storage, &addr)?,
+                    addr,
+                    peer_addr: peer,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(MembersStatsResp { members })
+    }
+
+    pub fn top_peers(
+        deps: Deps,
+        env: Env,
+        limit: Option<u32>,
+        start_after: Option<String>,
+    ) -> StdResult<TopPeersResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let start_after = match start_after {
+            Some(peer) => {
+                let peer = deps.api.addr_validate(&peer)?;
+                let owner = MEMBERS
+                    .may_load(deps.storage, peer)?
+                    .ok_or_else(|| StdError::generic_err("No such member"))?;
+                let rank = RANKS.load(deps.storage, &owner)?;
+                Some((rank, owner))
+            }
+            None => None,
+        };
+
+        let peers = RANKED
+            .keys(
+                deps.storage,
+                None,
+                start_after
+                    .as_ref()
+                    .map(|(rank, owner)| Bound::exclusive((*rank, owner))),
+                Order::Descending,
+            )
+            .take(limit)
+            .map(|key| -> StdResult<_> {
+                let (_, owner) = key?;
+                let peer = OWNERS.load(deps.storage, &owner)?;
+
+                Ok(RankedPeer {
+                    peer,
+                    weight: Uint128::new(current_weight(deps, &env, &owner)?),
+                    owner,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(TopPeersResp { peers })
+    }
+
+    pub fn sponsors(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<SponsorsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let start_after = match start_after {
+            Some(addr) => {
+                let addr = deps.api.addr_validate(&addr)?;
+                let rank = SPONSORS
+                    .may_load(deps.storage, &addr)?
+                    .ok_or_else(|| StdError::generic_err("No such sponsor"))?
+                    .rank;
+                Some((rank, addr))
+            }
+            None => None,
+        };
+
+        let sponsors = SPONSOR_RANKS
+            .keys(
+                deps.storage,
+                None,
+                start_after
+                    .as_ref()
+                    .map(|(rank, addr)| Bound::exclusive((*rank, addr))),
+                Order::Descending,
+            )
+            .take(limit)
+            .map(|key| -> StdResult<_> {
+                let (_, addr) = key?;
+                let sponsorship = SPONSORS.load(deps.storage, &addr)?;
+
+                Ok(SponsorResp {
+                    addr,
+                    name: sponsorship.name,
+                    message: sponsorship.message,
+                    total: sponsorship.total,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(SponsorsResp { sponsors })
     }
 }
@@ -0,0 +1,255 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Fraction, Timestamp};
+
+use crate::msg::PeerAmount;
+use crate::state::DecayedWeight;
+
+/// Part of `funds` belonging to the peer with `weight` out of `total`, zero amounts skipped
+fn peer_share(funds: &[Coin], weight: u128, total: u128) -> Vec<Coin> {
+    if total == 0 {
+        return vec![];
+    }
+
+    funds
+        .iter()
+        .map(|coin| Coin {
+            denom: coin.denom.clone(),
+            amount: coin.amount.multiply_ratio(weight, total),
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect()
+}
+
+/// Bounds of a single peer share in a distribution
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ShareLimits {
+    pub min: Option<Decimal>,
+    pub max: Option<Decimal>,
+}
+
+impl ShareLimits {
+    pub fn is_unbounded(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    /// Shares of the peers with `weights` summing up to at most one. Every peer takes the
+    /// floor first, and the rest is split proportionally to weights. Peers going over the cap
+    /// are capped, and their excess is split again between the uncapped peers until no peer
+    /// goes over. Floor and cap are adjusted towards the equal split, so they can always be
+    /// met.
+    fn shares(&self, weights: &[u128]) -> Vec<Decimal> {
+        let equal = Decimal::from_ratio(1u128, weights.len() as u128);
+        let floor = self.min.unwrap_or_default().min(equal);
+        let cap = self.max.map_or(Decimal::one(), |max| max.max(equal));
+
+        let mut shares = vec![floor; weights.len()];
+        let mut capped = vec![false; weights.len()];
+        let mut remaining =
+            Decimal::one() - floor * Decimal::from_ratio(weights.len() as u128, 1u128);
+
+        while !remaining.is_zero() {
+            let free: Vec<_> = (0..weights.len()).filter(|idx| !capped[*idx]).collect();
+            if free.is_empty() {
+                break;
+            }
+
+            // If only peers without weight are left uncapped, they split the rest equally
+            let free_weight: u128 = free.iter().map(|idx| weights[*idx]).sum();
+            let weight = |idx: usize| if free_weight == 0 { 1 } else { weights[idx] };
+            let free_weight = free.iter().map(|idx| weight(*idx)).sum::<u128>();
+
+            let over_cap: Vec<_> = free
+                .iter()
+                .copied()
+                .filter(|idx| {
+                    shares[*idx] + remaining * Decimal::from_ratio(weight(*idx), free_weight) > cap
+                })
+                .collect();
+
+            if over_cap.is_empty() {
+                for idx in free {
+                    shares[idx] += remaining * Decimal::from_ratio(weight(idx), free_weight);
+                }
+                break;
+            }
+
+            for idx in over_cap {
+                remaining -= cap - shares[idx];
+                shares[idx] = cap;
+                capped[idx] = true;
+            }
+        }
+
+        shares
+    }
+}
+
+/// Fractions of the distributions going to the peers with `weights`, within the share `limits`
+pub fn fractions(weights: &[(Addr, u128)], limits: &ShareLimits) -> Vec<(Addr, Decimal)> {
+    let total: u128 = weights.iter().map(|(_, weight)| weight).sum();
+    if total == 0 {
+        return weights
+            .iter()
+            .map(|(peer, _)| (peer.clone(), Decimal::zero()))
+            .collect();
+    }
+
+    let shares = if limits.is_unbounded() {
+        weights
+            .iter()
+            .map(|(_, weight)| Decimal::from_ratio(*weight, total))
+            .collect()
+    } else {
+        limits.shares(
+            &weights
+                .iter()
+                .map(|(_, weight)| *weight)
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    weights
+        .iter()
+        .map(|(peer, _)| peer.clone())
+        .zip(shares)
+        .collect()
+}
+
+/// Splits `funds` between peers proportionally to their weights, within the share `limits`.
+/// Peers with nothing to receive are skipped, and without any weight nothing is split.
+pub fn split(funds: &[Coin], weights: &[(Addr, u128)], limits: &ShareLimits) -> Vec<PeerAmount> {
+    let total = weights.iter().map(|(_, weight)| weight).sum();
+    if total == 0 {
+        return vec![];
+    }
+
+    let amounts: Vec<_> = if limits.is_unbounded() {
+        weights
+            .iter()
+            .map(|(peer, weight)| PeerAmount {
+                peer: peer.clone(),
+                amount: peer_share(funds, *weight, total),
+            })
+            .collect()
+    } else {
+        fractions(weights, limits)
+            .into_iter()
+            .map(|(peer, share)| PeerAmount {
+                peer,
+                amount: funds
+                    .iter()
+                    .map(|coin| Coin {
+                        denom: coin.denom.clone(),
+                        amount: coin.amount * share,
+                    })
+                    .filter(|coin| !coin.amount.is_zero())
+                    .collect(),
+            })
+            .collect()
+    };
+
+    amounts
+        .into_iter()
+        .filter(|amount| !amount.amount.is_empty())
+        .collect()
+}
+
+/// Precision of the fractional half-life exponent, in bits
+const DECAY_PRECISION: u32 = 20;
+
+/// `value` decayed by half for every `half_life` seconds of `elapsed`
+pub fn decay(value: Decimal, elapsed: u64, half_life: u64) -> Decimal {
+    let halvings = elapsed / half_life;
+    if halvings >= 64 {
+        return Decimal::zero();
+    }
+    let mut value = value * Decimal::from_ratio(1u128, 1u128 << halvings);
+
+    // The remaining fraction of the half-life, bit by bit: 0.5^(1/2), 0.5^(1/4), ...
+    let mut remainder = (elapsed % half_life) as u128;
+    let mut factor = Decimal::percent(50);
+    for _ in 0..DECAY_PRECISION {
+        if remainder == 0 {
+            break;
+        }
+
+        factor = factor.sqrt();
+        remainder *= 2;
+        if remainder >= half_life as u128 {
+            remainder -= half_life as u128;
+            value *= factor;
+        }
+    }
+
+    value
+}
+
+/// Fractional bits of the `log2` result
+const LOG2_PRECISION: u32 = 32;
+
+/// Binary logarithm of `value`, with `LOG2_PRECISION` fractional bits. Zero for zero.
+fn log2(value: u128) -> u128 {
+    if value == 0 {
+        return 0;
+    }
+
+    let int = 127 - value.leading_zeros();
+
+    // Mantissa in [1, 2) with 62 fractional bits - squaring it fits in u128
+    const ONE: u128 = 1 << 62;
+    let mut mantissa = if int >= 62 {
+        value >> (int - 62)
+    } else {
+        value << (62 - int)
+    };
+
+    let mut result = (int as u128) << LOG2_PRECISION;
+    for bit in (0..LOG2_PRECISION).rev() {
+        mantissa = (mantissa * mantissa) >> 62;
+        if mantissa >= 2 * ONE {
+            mantissa >>= 1;
+            result |= 1 << bit;
+        }
+    }
+
+    result
+}
+
+impl DecayedWeight {
+    pub fn new(now: Timestamp) -> Self {
+        Self {
+            score: Decimal::zero(),
+            updated: now,
+        }
+    }
+
+    /// The score decayed until `now`
+    pub fn at(&self, now: Timestamp, half_life: u64) -> Decimal {
+        let elapsed = now.seconds().saturating_sub(self.updated.seconds());
+        decay(self.score, elapsed, half_life)
+    }
+
+    /// Key ordering decayed weights the same way as their values at any common time. All
+    /// scores decay at the same pace, so `log2(score) + updated / half_life` never changes
+    /// until the weight is updated.
+    pub fn rank(&self, half_life: u64) -> u128 {
+        if self.score.is_zero() {
+            return 0;
+        }
+
+        let updated = ((self.updated.seconds() as u128) << LOG2_PRECISION) / half_life as u128;
+        log2(self.score.numerator().u128()) + updated
+    }
+
+    /// Applies the change of the member weight from `old` to `new` at `now`. Increments are
+    /// added with the full value, while decrements scale the score down proportionally, so
+    /// dropping the weight to zero clears the score.
+    pub fn update(&mut self, old: u64, new: u64, now: Timestamp, half_life: u64) {
+        let score = self.at(now, half_life);
+        self.score = if new >= old {
+            score + Decimal::from_ratio(new - old, 1u128)
+        } else {
+            score * Decimal::from_ratio(new, old)
+        };
+        self.updated = now;
+    }
+}
@@ -1,4 +1,5 @@
 mod contract;
+mod distribution;
 pub mod msg;
 pub mod state;
 
@@ -10,11 +11,11 @@ use cosmwasm_std::{
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: msg::InstantiateMsg,
 ) -> StdResult<Response> {
-    contract::instantiate(deps, msg)
+    contract::instantiate(deps, env, msg)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -28,14 +29,56 @@ pub fn execute(
     use msg::ExecMsg::*;
 
     match msg {
-        Join {} => exec::join(deps, info),
-        Leave {} => exec::leave(deps, info),
+        Join { invite } => exec::join(deps, env, info, invite),
+        Leave {} => exec::leave(deps, env, info),
+        Renew {} => exec::renew(deps, env, info),
+        Prune { limit } => exec::prune(deps, env, info, limit),
+        LeaveWaitlist {} => exec::leave_waitlist(deps, info),
+        AddInvite {
+            hash,
+            expires,
+            uses,
+        } => exec::add_invite(deps, info, hash, expires, uses),
+        RemoveInvite { hash } => exec::remove_invite(deps, info, hash),
         Donate {} => exec::donate(deps, env, info),
+        Sponsor { name, message } => exec::sponsor(deps, env, info, name, message),
+        DonateTo {
+            peers,
+            count_as_donator,
+        } => exec::donate_to(deps, env, info, peers, count_as_donator),
+        UpdateProfile { name, tags } => exec::update_profile(deps, info, name, tags),
+        DistributeEpochs { limit } => exec::distribute_epochs(deps, env, info, limit),
+        Pause {} => exec::set_paused(deps, info, true),
+        Unpause {} => exec::set_paused(deps, info, false),
+        Apply { metadata } => exec::apply(deps, env, info, metadata),
+        Approve { applicant } => exec::approve(deps, info, applicant),
+        Reject { applicant, reason } => exec::reject(deps, info, applicant, reason),
+        UpdateConfig {
+            collective_ratio,
+            incremental_donation,
+        } => exec::update_config(deps, info, collective_ratio, incremental_donation),
+        UpgradePeers { code_id } => exec::upgrade_peers(deps, info, code_id),
+        Propose {
+            title,
+            description,
+            action,
+        } => exec::propose(deps, env, info, title, description, action),
+        Vote { proposal_id, vote } => exec::vote(deps, env, info, proposal_id, vote),
+        ExecuteProposal { proposal_id } => exec::execute_proposal(deps, env, info, proposal_id),
+        AddHook { addr } => exec::add_hook(deps, info, addr),
+        RemoveHook { addr } => exec::remove_hook(deps, info, addr),
+        AddEventHook { addr } => exec::add_event_hook(deps, info, addr),
+        RemoveEventHook { addr } => exec::remove_event_hook(deps, info, addr),
+        RemoveMember { owner, reason } => {
+            exec::remove_member_by_admin(deps, env, info, owner, reason)
+        }
+        Unban { owner } => exec::unban(deps, info, owner),
+        Slash { owner, amount } => exec::slash(deps, info, owner, amount),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use contract::query;
     use msg::QueryMsg::*;
 
@@ -45,13 +88,74 @@ pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
         MembersList { start_after, limit } => {
             to_binary(&query::members_list(deps, start_after, limit)?)
         }
+        Paused {} => to_binary(&query::paused(deps)?),
+        Applications { start_after, limit } => {
+            to_binary(&query::applications(deps, start_after, limit)?)
+        }
+        Proposal { proposal_id } => to_binary(&query::proposal(deps, env, proposal_id)?),
+        Proposals { start_after, limit } => {
+            to_binary(&query::proposals(deps, env, start_after, limit)?)
+        }
+        Vote { proposal_id, voter } => to_binary(&query::vote(deps, proposal_id, voter)?),
+        Votes {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_binary(&query::votes(deps, proposal_id, start_after, limit)?),
+        Admin {} => to_binary(&query::admin(deps)?),
+        TotalWeight {} => to_binary(&query::total_weight(deps)?),
+        ListMembers { start_after, limit } => {
+            to_binary(&query::list_members(deps, start_after, limit)?)
+        }
+        Member { addr, at_height } => to_binary(&query::member(deps, addr, at_height)?),
+        Hooks {} => to_binary(&query::hooks(deps)?),
+        EventHooks {} => to_binary(&query::event_hooks(deps)?),
+        PendingLeave { addr } => to_binary(&query::pending_leave(deps, &addr)?),
+        RemovedMembers { start_after, limit } => {
+            to_binary(&query::removed_members(deps, start_after, limit)?)
+        }
+        Stake { addr } => to_binary(&query::stake(deps, &addr)?),
+        Waitlist { start_after, limit } => to_binary(&query::waitlist(deps, start_after, limit)?),
+        WaitlistPosition { addr } => to_binary(&query::waitlist_position(deps, &addr)?),
+        Invites { start_after, limit } => to_binary(&query::invites(deps, start_after, limit)?),
+        Epoch {} => to_binary(&query::epoch(deps, env)?),
+        EpochHistory { start_after, limit } => {
+            to_binary(&query::epoch_history(deps, start_after, limit)?)
+        }
+        SimulateDistribution { funds } => {
+            to_binary(&query::simulate_distribution(deps, env, funds)?)
+        }
+        ExpectedShare { peer } => to_binary(&query::expected_share(deps, env, peer)?),
+        Distributions { start_after, limit } => {
+            to_binary(&query::distributions(deps, start_after, limit)?)
+        }
+        Distribution { id } => to_binary(&query::distribution(deps, id)?),
+        PeerEarnings { peer } => to_binary(&query::peer_earnings(deps, &peer)?),
+        MembersStats { start_after, limit } => {
+            to_binary(&query::members_stats(deps, env, start_after, limit)?)
+        }
+        TopPeers { limit, start_after } => {
+            to_binary(&query::top_peers(deps, env, limit, start_after)?)
+        }
+        MembersByTag {
+            tag,
+            start_after,
+            limit,
+        } => to_binary(&query::members_by_tag(deps, tag, start_after, limit)?),
+        MembersByNamePrefix { prefix, limit } => {
+            to_binary(&query::members_by_name_prefix(deps, prefix, limit)?)
+        }
+        Sponsors { start_after, limit } => to_binary(&query::sponsors(deps, start_after, limit)?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
     match msg.id {
-        contract::PEER_INSTANTIATE_ID => contract::exec::peer_instantiate_reply(deps, msg.result),
+        contract::PEER_INSTANTIATE_ID => {
+            contract::exec::peer_instantiate_reply(deps, env, msg.result)
+        }
+        contract::EVENT_HOOK_ID => contract::exec::event_hook_reply(msg.result),
         _ => Err(StdError::generic_err("unknown reply id")),
     }
 }
@@ -59,20 +163,38 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
 #[cfg(test)]
 mod tests {
     use crate::msg::{
-        ConfigResp, ExecMsg, InstantiateMsg, MemberPeerAddrResp, MembersListResp, QueryMsg,
+        ApplicationsResp, ClosedEpochResp, ConfigResp, DistributionResp, DistributionsResp,
+        EpochHistoryResp, EpochResp, ExecMsg, ExpectedShareResp, GovernanceConfig, InstantiateMsg,
+        InviteResp, InvitesResp, JoinRequirement, Member, MemberPeerAddrResp, MemberStats,
+        MembersListResp, MembersStatsResp, MembershipTerm, PausedResp, PeerAmount,
+        PeerEarningsResp, PendingLeaveResp, PoolHookExecuteMsg, PoolHookMsg, ProposalAction,
+        ProposalResp, ProposalStatus, ProposalsResp, QueryMsg, RankedPeer, RemovedMemberResp,
+        RemovedMembersResp, SimulateDistributionResp, SponsorResp, SponsorsResp, StakeResp,
+        TopPeersResp, VoteOption, VotesResp, VotingWeight, WaitlistPositionResp, WaitlistResp,
+    };
+    use cw4::{
+        Cw4QueryMsg, HooksResponse, Member as GroupMember, MemberChangedHookMsg, MemberDiff,
+        MemberListResponse, MemberResponse, TotalWeightResponse,
+    };
+    use peer::msg::{
+        ConfigResp as PeerConfigResp, DonatorsResp, ExecMsg as PeerExec, ManagerResp,
+        MembershipResp, Profile, ProfileResp, QueryMsg as PeerQuery, StandaloneMode, VestingResp,
+        VestingSchedule,
     };
-    use peer::msg::{DonatorsResp, ExecMsg as PeerExec, ManagerResp, QueryMsg as PeerQuery};
 
     use super::*;
 
-    use cosmwasm_std::{coin, coins, Addr, Decimal, Empty};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, coins, Addr, Decimal, Empty, Fraction, ReplyOn, Uint128};
     use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
 
     use donation_peer as peer;
     use peer::msg::OwnerResp;
 
     fn peer() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(peer::execute, peer::instantiate, peer::query);
+        let contract = ContractWrapper::new(peer::execute, peer::instantiate, peer::query)
+            .with_migrate(peer::migrate);
         Box::new(contract)
     }
 
@@ -95,6 +217,7 @@ mod tests {
                     peer_code_id,
                     incremental_donation: coin(100, "utgd"),
                     collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
                 },
                 &[],
                 "manager",
@@ -138,6 +261,7 @@ mod tests {
                     peer_code_id,
                     incremental_donation: coin(100, "utgd"),
                     collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
                 },
                 &[],
                 "manager",
@@ -148,7 +272,7 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member"),
             manager.clone(),
-            &ExecMsg::Join {},
+            &ExecMsg::Join { invite: None },
             &[],
         )
         .unwrap();
@@ -206,6 +330,7 @@ mod tests {
                     peer_code_id,
                     incremental_donation: coin(100, "utgd"),
                     collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
                 },
                 &[],
                 "manager",
@@ -216,7 +341,7 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member"),
             manager.clone(),
-            &ExecMsg::Join {},
+            &ExecMsg::Join { invite: None },
             &[],
         )
         .unwrap();
@@ -242,7 +367,10 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member"),
             peer.addr.clone(),
-            &PeerExec::Withdraw {},
+            &PeerExec::Withdraw {
+                amount: None,
+                recipient: None,
+            },
             &[],
         )
         .unwrap();
@@ -289,6 +417,7 @@ mod tests {
                     peer_code_id,
                     incremental_donation: coin(100, "utgd"),
                     collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
                 },
                 &[],
                 "manager",
@@ -299,7 +428,7 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member1"),
             manager.clone(),
-            &ExecMsg::Join {},
+            &ExecMsg::Join { invite: None },
             &[],
         )
         .unwrap();
@@ -307,7 +436,7 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member2"),
             manager.clone(),
-            &ExecMsg::Join {},
+            &ExecMsg::Join { invite: None },
             &[],
         )
         .unwrap();
@@ -351,7 +480,10 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member1"),
             peer1.addr,
-            &PeerExec::Withdraw {},
+            &PeerExec::Withdraw {
+                amount: None,
+                recipient: None,
+            },
             &[],
         )
         .unwrap();
@@ -359,7 +491,10 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member2"),
             peer2.addr,
-            &PeerExec::Withdraw {},
+            &PeerExec::Withdraw {
+                amount: None,
+                recipient: None,
+            },
             &[],
         )
         .unwrap();
@@ -402,6 +537,7 @@ mod tests {
                     peer_code_id,
                     incremental_donation: coin(100, "utgd"),
                     collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
                 },
                 &[],
                 "manager",
@@ -412,7 +548,7 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member1"),
             manager.clone(),
-            &ExecMsg::Join {},
+            &ExecMsg::Join { invite: None },
             &[],
         )
         .unwrap();
@@ -420,7 +556,7 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member2"),
             manager.clone(),
-            &ExecMsg::Join {},
+            &ExecMsg::Join { invite: None },
             &[],
         )
         .unwrap();
@@ -464,7 +600,10 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member1"),
             peer1.addr,
-            &PeerExec::Withdraw {},
+            &PeerExec::Withdraw {
+                amount: None,
+                recipient: None,
+            },
             &[],
         )
         .unwrap();
@@ -472,7 +611,10 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("member2"),
             peer2.addr,
-            &PeerExec::Withdraw {},
+            &PeerExec::Withdraw {
+                amount: None,
+                recipient: None,
+            },
             &[],
         )
         .unwrap();
@@ -487,4 +629,4561 @@ mod tests {
             app.wrap().query_balance("member2", "utgd").unwrap()
         );
     }
+
+    #[test]
+    fn vested_withdrawals() {
+        // Donation of 100utgd is split into two tranches: 40utgd kept by the peer directly, and
+        // 60utgd distributed back by the manager - the second one is first seen by the peer on
+        // the withdrawal 500 seconds later.
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(100, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    vesting: Some(VestingSchedule {
+                        cliff: 100,
+                        duration: 1000,
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap();
+
+        let peer: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member".to_owned(),
+                },
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        let withdraw = PeerExec::Withdraw {
+            amount: None,
+            recipient: None,
+        };
+
+        app.execute_contract(Addr::unchecked("member"), peer.addr.clone(), &withdraw, &[])
+            .unwrap_err();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(500));
+
+        app.execute_contract(Addr::unchecked("member"), peer.addr.clone(), &withdraw, &[])
+            .unwrap();
+
+        assert_eq!(
+            coin(20, "utgd"),
+            app.wrap().query_balance("member", "utgd").unwrap()
+        );
+
+        let vesting: VestingResp = app
+            .wrap()
+            .query_wasm_smart(peer.addr.clone(), &PeerQuery::Vesting {})
+            .unwrap();
+
+        assert_eq!(coins(80, "utgd"), vesting.locked);
+        assert_eq!(coins(20, "utgd"), vesting.vested);
+        assert_eq!(coins(20, "utgd"), vesting.withdrawn);
+        assert!(vesting.withdrawable.is_empty());
+
+        app.update_block(|block| block.time = block.time.plus_seconds(1000));
+
+        app.execute_contract(Addr::unchecked("member"), peer.addr, &withdraw, &[])
+            .unwrap();
+
+        assert_eq!(
+            coin(100, "utgd"),
+            app.wrap().query_balance("member", "utgd").unwrap()
+        );
+    }
+
+    #[test]
+    fn vesting_tranches_bounded() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(1000, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(10, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    vesting: Some(VestingSchedule {
+                        cliff: 0,
+                        duration: 1000,
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap();
+
+        let peer: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member".to_owned(),
+                },
+            )
+            .unwrap();
+
+        // Dust donations every few seconds do not grow the vesting state
+        for _ in 0..100 {
+            app.update_block(|block| {
+                block.height += 1;
+                block.time = block.time.plus_seconds(5);
+            });
+
+            app.execute_contract(
+                Addr::unchecked("donator"),
+                peer.addr.clone(),
+                &PeerExec::Donate {},
+                &coins(10, "utgd"),
+            )
+            .unwrap();
+        }
+
+        let vesting = peer::state::VESTING
+            .query(&app.wrap(), peer.addr.clone())
+            .unwrap();
+        assert!(vesting.tranches.len() as u64 <= peer::state::MAX_TRANCHES);
+
+        // Nothing is lost by merging tranches - only the last distribution from the manager is
+        // first seen by the withdrawal, and it starts vesting then
+        app.update_block(|block| block.time = block.time.plus_seconds(1000));
+        app.execute_contract(
+            Addr::unchecked("member"),
+            peer.addr,
+            &PeerExec::Withdraw {
+                amount: None,
+                recipient: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            coin(994, "utgd"),
+            app.wrap().query_balance("member", "utgd").unwrap()
+        );
+    }
+
+    #[test]
+    fn pause() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(100, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    pauser: Some("pauser".to_owned()),
+                    epoch_length: Some(100),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap();
+
+        let peer: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member".to_owned(),
+                },
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member"),
+            manager.clone(),
+            &ExecMsg::Pause {},
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("pauser"),
+            manager.clone(),
+            &ExecMsg::Pause {},
+            &[],
+        )
+        .unwrap();
+
+        let paused: PausedResp = app
+            .wrap()
+            .query_wasm_smart(manager.clone(), &QueryMsg::Paused {})
+            .unwrap();
+        assert!(paused.paused);
+        assert_eq!(Some(Addr::unchecked("pauser")), paused.pauser);
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("pauser"),
+            manager.clone(),
+            &ExecMsg::Unpause {},
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(100);
+        });
+
+        app.execute_contract(
+            Addr::unchecked("pauser"),
+            manager.clone(),
+            &ExecMsg::Pause {},
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            manager.clone(),
+            &ExecMsg::DistributeEpochs { limit: None },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("pauser"),
+            manager.clone(),
+            &ExecMsg::Unpause {},
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            manager.clone(),
+            &ExecMsg::DistributeEpochs { limit: None },
+            &[],
+        )
+        .unwrap();
+
+        // Withdrawals are never blocked, but leaving the pool is
+        app.execute_contract(
+            Addr::unchecked("pauser"),
+            manager.clone(),
+            &ExecMsg::Pause {},
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(Addr::unchecked("member"), manager, &ExecMsg::Leave {}, &[])
+            .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("member"),
+            peer.addr,
+            &PeerExec::Withdraw {
+                amount: None,
+                recipient: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            coin(100, "utgd"),
+            app.wrap().query_balance("member", "utgd").unwrap()
+        );
+    }
+
+    #[test]
+    fn gated_membership() {
+        let mut app = App::default();
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    require_approval: true,
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("applicant1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap_err();
+
+        for applicant in ["applicant1", "applicant2"] {
+            app.execute_contract(
+                Addr::unchecked(applicant),
+                manager.clone(),
+                &ExecMsg::Apply {
+                    metadata: format!("{} portfolio", applicant),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let applications: ApplicationsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Applications {
+                    start_after: Some("applicant1".to_owned()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(1, applications.applications.len());
+        assert_eq!(
+            Addr::unchecked("applicant2"),
+            applications.applications[0].applicant
+        );
+        assert_eq!(
+            "applicant2 portfolio",
+            applications.applications[0].metadata
+        );
+
+        app.execute_contract(
+            Addr::unchecked("stranger"),
+            manager.clone(),
+            &ExecMsg::Approve {
+                applicant: "applicant1".to_owned(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::Approve {
+                applicant: "applicant1".to_owned(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("applicant1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap();
+
+        // Existing members can review applications too
+        app.execute_contract(
+            Addr::unchecked("applicant1"),
+            manager.clone(),
+            &ExecMsg::Reject {
+                applicant: "applicant2".to_owned(),
+                reason: Some("Spam".to_owned()),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("applicant2"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap_err();
+
+        let applications: ApplicationsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Applications {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(applications.applications.is_empty());
+
+        let members: MembersListResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MembersList {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(1, members.members.len());
+        assert_eq!(Addr::unchecked("applicant1"), members.members[0].addr);
+    }
+
+    #[test]
+    fn governance_config_update() {
+        let mut app = App::default();
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    governance: Some(GovernanceConfig {
+                        voting_period: 100,
+                        quorum: Decimal::percent(50),
+                        threshold: Decimal::percent(50),
+                        voting_weight: VotingWeight::Member,
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        for member in ["member1", "member2", "member3"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        }
+
+        // Voting weights are taken from the block before the proposal creation
+        app.update_block(|block| block.height += 1);
+
+        let propose = ExecMsg::Propose {
+            title: "Lower collective ratio".to_owned(),
+            description: "Peers should keep more of their donations".to_owned(),
+            action: ProposalAction::UpdateConfig {
+                collective_ratio: Some(Decimal::percent(20)),
+                incremental_donation: None,
+            },
+        };
+
+        app.execute_contract(Addr::unchecked("stranger"), manager.clone(), &propose, &[])
+            .unwrap_err();
+
+        app.execute_contract(Addr::unchecked("member1"), manager.clone(), &propose, &[])
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::ExecuteProposal { proposal_id: 1 },
+            &[],
+        )
+        .unwrap_err();
+
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Vote {
+                    proposal_id: 1,
+                    vote: VoteOption::Yes,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        // Majority already voted yes, proposal is decided
+        app.execute_contract(
+            Addr::unchecked("member3"),
+            manager.clone(),
+            &ExecMsg::Vote {
+                proposal_id: 1,
+                vote: VoteOption::No,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("stranger"),
+            manager.clone(),
+            &ExecMsg::ExecuteProposal { proposal_id: 1 },
+            &[],
+        )
+        .unwrap();
+
+        let proposal: ProposalResp = app
+            .wrap()
+            .query_wasm_smart(manager.clone(), &QueryMsg::Proposal { proposal_id: 1 })
+            .unwrap();
+        assert_eq!(ProposalStatus::Executed, proposal.status);
+        assert_eq!(
+            (3, 2, 0),
+            (proposal.total_weight, proposal.yes, proposal.no)
+        );
+
+        let votes: VotesResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Votes {
+                    proposal_id: 1,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(2, votes.votes.len());
+
+        let config: ConfigResp = app
+            .wrap()
+            .query_wasm_smart(manager.clone(), &QueryMsg::Config {})
+            .unwrap();
+        assert_eq!(Decimal::percent(20), config.collective_ratio);
+
+        let peer: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member3".to_owned(),
+                },
+            )
+            .unwrap();
+
+        let peer_config: PeerConfigResp = app
+            .wrap()
+            .query_wasm_smart(peer.addr, &PeerQuery::Config {})
+            .unwrap();
+        assert_eq!(Decimal::percent(20), peer_config.collective_ratio);
+    }
+
+    #[test]
+    fn governance_upgrade_and_removal() {
+        let mut app = App::default();
+        let peer_code_id = app.store_code(peer());
+        let new_peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    governance: Some(GovernanceConfig {
+                        voting_period: 100,
+                        quorum: Decimal::percent(50),
+                        threshold: Decimal::percent(50),
+                        voting_weight: VotingWeight::Member,
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        for member in ["member1", "member2", "member3"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        }
+
+        // Voting weights are taken from the block before the proposal creation
+        app.update_block(|block| block.height += 1);
+
+        let actions = [
+            ProposalAction::UpgradePeers {
+                code_id: new_peer_code_id,
+            },
+            ProposalAction::RemoveMember {
+                owner: "member3".to_owned(),
+                reason: "Inactive".to_owned(),
+            },
+        ];
+
+        for action in actions {
+            app.execute_contract(
+                Addr::unchecked("member1"),
+                manager.clone(),
+                &ExecMsg::Propose {
+                    title: "Proposal".to_owned(),
+                    description: String::new(),
+                    action,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let votes = [
+            ("member1", VoteOption::Yes, VoteOption::Yes),
+            ("member2", VoteOption::No, VoteOption::No),
+            ("member3", VoteOption::Abstain, VoteOption::No),
+        ];
+
+        for (member, upgrade, removal) in votes {
+            for (proposal_id, vote) in [(1, upgrade), (2, removal)] {
+                app.execute_contract(
+                    Addr::unchecked(member),
+                    manager.clone(),
+                    &ExecMsg::Vote { proposal_id, vote },
+                    &[],
+                )
+                .unwrap();
+            }
+        }
+
+        let proposals: ProposalsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Proposals {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(proposals
+            .proposals
+            .iter()
+            .all(|proposal| proposal.status == ProposalStatus::Open));
+
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::ExecuteProposal { proposal_id: 2 },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::ExecuteProposal { proposal_id: 1 },
+            &[],
+        )
+        .unwrap();
+
+        let peer: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member3".to_owned(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            new_peer_code_id,
+            app.contract_data(&peer.addr).unwrap().code_id as u64
+        );
+
+        let proposal: ProposalResp = app
+            .wrap()
+            .query_wasm_smart(manager, &QueryMsg::Proposal { proposal_id: 2 })
+            .unwrap();
+        assert_eq!(ProposalStatus::Rejected, proposal.status);
+    }
+
+    // Hook recording all the membership changes it is notified about
+    fn cw4_hook() -> Box<dyn Contract<Empty>> {
+        const DIFFS: Item<Vec<MemberDiff>> = Item::new("diffs");
+
+        // Receiver side of the cw4 hook, which is not exported by the `cw4` crate
+        #[derive(Clone, Debug, serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum HookExecMsg {
+            MemberChangedHook(MemberChangedHookMsg),
+        }
+
+        let contract = ContractWrapper::new(
+            |deps: DepsMut, _env: Env, _info: MessageInfo, msg: HookExecMsg| {
+                let HookExecMsg::MemberChangedHook(msg) = msg;
+                let mut diffs = DIFFS.may_load(deps.storage)?.unwrap_or_default();
+                diffs.extend(msg.diffs);
+                DIFFS.save(deps.storage, &diffs)?;
+                StdResult::Ok(Response::new())
+            },
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| {
+                StdResult::Ok(Response::new())
+            },
+            |deps: Deps, _env: Env, _msg: Empty| {
+                to_binary(&DIFFS.may_load(deps.storage)?.unwrap_or_default())
+            },
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn cw4_group() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(100, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+        let hook_code_id = app.store_code(cw4_hook());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let hook = app
+            .instantiate_contract(
+                hook_code_id,
+                Addr::unchecked("admin"),
+                &Empty {},
+                &[],
+                "hook",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::AddHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::AddHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let peer1: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+
+        app.update_block(|block| block.height += 1);
+        let donation_height = app.block_info().height;
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer1.addr,
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        let member: MemberResponse = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &Cw4QueryMsg::Member {
+                    addr: "member1".to_owned(),
+                    at_height: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(Some(1), member.weight);
+
+        let member: MemberResponse = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &Cw4QueryMsg::Member {
+                    addr: "member1".to_owned(),
+                    at_height: Some(donation_height),
+                },
+            )
+            .unwrap();
+        assert_eq!(Some(0), member.weight);
+
+        let total: TotalWeightResponse = app
+            .wrap()
+            .query_wasm_smart(manager.clone(), &Cw4QueryMsg::TotalWeight {})
+            .unwrap();
+        assert_eq!(1, total.weight);
+
+        let members: MemberListResponse = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &Cw4QueryMsg::ListMembers {
+                    start_after: Some("member1".to_owned()),
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![GroupMember {
+                addr: "member2".to_owned(),
+                weight: 0
+            }],
+            members.members
+        );
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        let hooks: HooksResponse = app
+            .wrap()
+            .query_wasm_smart(manager, &Cw4QueryMsg::Hooks {})
+            .unwrap();
+        assert_eq!(vec![hook.to_string()], hooks.hooks);
+
+        let diffs: Vec<MemberDiff> = app.wrap().query_wasm_smart(hook, &Empty {}).unwrap();
+        let diffs: Vec<_> = diffs
+            .into_iter()
+            .map(|diff| (diff.key, diff.old, diff.new))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("member1".to_owned(), None, Some(0)),
+                ("member2".to_owned(), None, Some(0)),
+                ("member1".to_owned(), Some(0), Some(1)),
+                ("member2".to_owned(), Some(0), None),
+            ],
+            diffs
+        );
+    }
+
+    // Hook recording all the pool events it is notified about
+    fn pool_hook() -> Box<dyn Contract<Empty>> {
+        const EVENTS: Item<Vec<PoolHookMsg>> = Item::new("events");
+
+        let contract = ContractWrapper::new(
+            |deps: DepsMut, _env: Env, _info: MessageInfo, msg: PoolHookExecuteMsg| {
+                let PoolHookExecuteMsg::PoolHook(msg) = msg;
+                let mut events = EVENTS.may_load(deps.storage)?.unwrap_or_default();
+                events.push(msg);
+                EVENTS.save(deps.storage, &events)?;
+                StdResult::Ok(Response::new())
+            },
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| {
+                StdResult::Ok(Response::new())
+            },
+            |deps: Deps, _env: Env, _msg: Empty| {
+                to_binary(&EVENTS.may_load(deps.storage)?.unwrap_or_default())
+            },
+        );
+        Box::new(contract)
+    }
+
+    // Hook rejecting every notification
+    fn failing_pool_hook() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: PoolHookExecuteMsg| {
+                StdResult::<Response>::Err(StdError::generic_err("Hook failure"))
+            },
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| {
+                StdResult::Ok(Response::new())
+            },
+            |_deps: Deps, _env: Env, _msg: Empty| to_binary(&Empty {}),
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn pool_hooks() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(100, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+        let hook_code_id = app.store_code(pool_hook());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let hook = app
+            .instantiate_contract(
+                hook_code_id,
+                Addr::unchecked("admin"),
+                &Empty {},
+                &[],
+                "hook",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::AddEventHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::AddEventHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::AddEventHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let peer1: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+
+        let peer2: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member2".to_owned(),
+                },
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer1.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        let hooks: HooksResponse = app
+            .wrap()
+            .query_wasm_smart(manager.clone(), &QueryMsg::EventHooks {})
+            .unwrap();
+        assert_eq!(vec![hook.to_string()], hooks.hooks);
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::RemoveEventHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let hooks: HooksResponse = app
+            .wrap()
+            .query_wasm_smart(manager, &QueryMsg::EventHooks {})
+            .unwrap();
+        assert!(hooks.hooks.is_empty());
+
+        let events: Vec<PoolHookMsg> = app.wrap().query_wasm_smart(hook, &Empty {}).unwrap();
+        assert_eq!(
+            vec![
+                PoolHookMsg::MemberJoined {
+                    member: Addr::unchecked("member1"),
+                    peer: peer1.addr.clone(),
+                },
+                PoolHookMsg::MemberJoined {
+                    member: Addr::unchecked("member2"),
+                    peer: peer2.addr.clone(),
+                },
+                PoolHookMsg::Distribution {
+                    sender: peer1.addr.clone(),
+                    amounts: vec![PeerAmount {
+                        peer: peer1.addr,
+                        amount: coins(60, "utgd"),
+                    }],
+                },
+                PoolHookMsg::MemberLeft {
+                    member: Addr::unchecked("member2"),
+                    peer: peer2.addr,
+                },
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn leave_detaches_peer() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(300, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let peer1: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+
+        let membership: MembershipResp = app
+            .wrap()
+            .query_wasm_smart(peer1.addr.clone(), &PeerQuery::Membership {})
+            .unwrap();
+        assert_eq!(
+            MembershipResp {
+                manager: manager.clone(),
+                member: true,
+                standalone_mode: StandaloneMode::KeepAll,
+            },
+            membership
+        );
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        let membership: MembershipResp = app
+            .wrap()
+            .query_wasm_smart(peer1.addr.clone(), &PeerQuery::Membership {})
+            .unwrap();
+        assert!(!membership.member);
+
+        // Only the manager can detach the peer
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            peer1.addr.clone(),
+            &PeerExec::Detach {},
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer1.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peer1.addr, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            100
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&manager, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            0
+        );
+
+        let donators: DonatorsResp = app
+            .wrap()
+            .query_wasm_smart(peer1.addr.clone(), &PeerQuery::Donators {})
+            .unwrap();
+        assert_eq!(1, donators.donators);
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            peer1.addr.clone(),
+            &PeerExec::SetStandaloneMode {
+                mode: StandaloneMode::Reject,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            peer1.addr.clone(),
+            &PeerExec::SetStandaloneMode {
+                mode: StandaloneMode::Reject,
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer1.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap_err();
+
+        let membership: MembershipResp = app
+            .wrap()
+            .query_wasm_smart(peer1.addr, &PeerQuery::Membership {})
+            .unwrap();
+        assert_eq!(StandaloneMode::Reject, membership.standalone_mode);
+    }
+
+    #[test]
+    fn leave_with_settlement() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(240, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    leave_notice_period: Some(100),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        for peer in &peers {
+            app.execute_contract(
+                Addr::unchecked("donator"),
+                peer.clone(),
+                &PeerExec::Donate {},
+                &coins(100, "utgd"),
+            )
+            .unwrap();
+        }
+
+        // Funds waiting on the manager for the next distribution
+        app.send_tokens(
+            Addr::unchecked("donator"),
+            manager.clone(),
+            &coins(40, "utgd"),
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        let pending: PendingLeaveResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::PendingLeave {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+        let leave_at = app.block_info().time.plus_seconds(100);
+        assert_eq!(Some(leave_at), pending.leave_at);
+
+        // Notice period is not over yet
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap_err();
+
+        app.update_block(|block| block.time = leave_at);
+
+        let resp = app
+            .execute_contract(
+                Addr::unchecked("member1"),
+                manager.clone(),
+                &ExecMsg::Leave {},
+                &[],
+            )
+            .unwrap();
+
+        let settlement = resp
+            .events
+            .iter()
+            .find(|event| event.ty == "wasm-settlement")
+            .unwrap();
+        assert!(settlement
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "amount" && attr.value == "20utgd"));
+
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            150
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&manager, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            20
+        );
+
+        let pending: PendingLeaveResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::PendingLeave {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(None, pending.leave_at);
+
+        let members: MembersListResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MembersList {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(1, members.members.len());
+    }
+
+    #[test]
+    fn admin_removal() {
+        let mut app = App::default();
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let peer1: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::RemoveMember {
+                owner: "member1".to_owned(),
+                reason: "Spam".to_owned(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::RemoveMember {
+                owner: "member1".to_owned(),
+                reason: "Spam".to_owned(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let membership: MembershipResp = app
+            .wrap()
+            .query_wasm_smart(peer1.addr.clone(), &PeerQuery::Membership {})
+            .unwrap();
+        assert!(!membership.member);
+
+        // Banned members cannot rejoin
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap_err();
+
+        let removed: RemovedMembersResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::RemovedMembers {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![RemovedMemberResp {
+                addr: Addr::unchecked("member1"),
+                peer: peer1.addr,
+                reason: "Spam".to_owned(),
+                removed_at: app.block_info().time,
+                banned: true,
+            }],
+            removed.members
+        );
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::Unban {
+                owner: "member1".to_owned(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap();
+
+        let removed: RemovedMembersResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::RemovedMembers {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(!removed.members[0].banned);
+    }
+
+    #[test]
+    fn join_stake() {
+        let mut app = App::new(|router, _api, storage| {
+            for (addr, amount) in [("donator", 100), ("member1", 50), ("member2", 50)] {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(addr), coins(amount, "utgd"))
+                    .unwrap();
+            }
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    join_requirement: Some(JoinRequirement::Stake {
+                        amount: coin(50, "utgd"),
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &coins(20, "utgd"),
+        )
+        .unwrap_err();
+
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &coins(50, "utgd"),
+            )
+            .unwrap();
+        }
+
+        let stake: StakeResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Stake {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(Some(coin(50, "utgd")), stake.stake);
+
+        let peer1: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer1.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // Stakes are not distributed
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peer1.addr, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            100
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&manager, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            100
+        );
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Slash {
+                owner: "member2".to_owned(),
+                amount: Some(Uint128::new(20)),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::Slash {
+                owner: "member2".to_owned(),
+                amount: Some(Uint128::new(20)),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let stake: StakeResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Stake {
+                    addr: "member2".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(Some(coin(30, "utgd")), stake.stake);
+
+        // Slashed funds are settled as a part of the pool
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance("member1", "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            50
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peer1.addr, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            120
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&manager, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            30
+        );
+
+        // Expelled member loses the stake left
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::RemoveMember {
+                owner: "member2".to_owned(),
+                reason: "Abuse".to_owned(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let stake: StakeResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Stake {
+                    addr: "member2".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(None, stake.stake);
+
+        assert_eq!(
+            app.wrap()
+                .query_balance("member2", "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            0
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&manager, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            30
+        );
+    }
+
+    #[test]
+    fn join_fee() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("member1"), coins(10, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    join_requirement: Some(JoinRequirement::Fee {
+                        amount: coin(10, "utgd"),
+                        treasury: Some("treasury".to_owned()),
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &coins(10, "utgd"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance("treasury", "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            10
+        );
+
+        // Fee is not refunded
+        app.execute_contract(Addr::unchecked("member1"), manager, &ExecMsg::Leave {}, &[])
+            .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance("member1", "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            0
+        );
+    }
+
+    #[test]
+    fn membership_terms() {
+        let mut app = App::new(|router, _api, storage| {
+            for (addr, amount) in [("donator", 300), ("member1", 15), ("member2", 10)] {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(addr), coins(amount, "utgd"))
+                    .unwrap();
+            }
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    membership_term: Some(MembershipTerm {
+                        duration: 100,
+                        renewal_fee: Some(coin(5, "utgd")),
+                    }),
+                    join_requirement: Some(JoinRequirement::Stake {
+                        amount: coin(10, "utgd"),
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let joined_at = app.block_info().time;
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &coins(10, "utgd"),
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        for peer in &peers {
+            app.execute_contract(
+                Addr::unchecked("donator"),
+                peer.clone(),
+                &PeerExec::Donate {},
+                &coins(100, "utgd"),
+            )
+            .unwrap();
+        }
+
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+
+        // Renewal fee is required
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Renew {},
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Renew {},
+            &coins(5, "utgd"),
+        )
+        .unwrap();
+
+        let members: MembersListResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MembersList {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let mut expires: Vec<_> = members
+            .members
+            .into_iter()
+            .map(|member| (member.addr, member.expires))
+            .collect();
+        expires.sort();
+        assert_eq!(
+            vec![
+                (
+                    Addr::unchecked("member1"),
+                    Some(joined_at.plus_seconds(200))
+                ),
+                (
+                    Addr::unchecked("member2"),
+                    Some(joined_at.plus_seconds(100))
+                ),
+            ],
+            expires
+        );
+
+        app.update_block(|block| block.time = joined_at.plus_seconds(100));
+
+        // Expired member takes no part in the distribution
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            235
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[1], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            70
+        );
+
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            manager.clone(),
+            &ExecMsg::Prune { limit: None },
+            &[],
+        )
+        .unwrap();
+
+        let members: MembersListResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MembersList {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(1, members.members.len());
+        assert_eq!(Addr::unchecked("member1"), members.members[0].addr);
+
+        // Pruned member gets the stake back
+        assert_eq!(
+            coin(10, "utgd"),
+            app.wrap().query_balance("member2", "utgd").unwrap()
+        );
+    }
+
+    #[test]
+    fn waitlist() {
+        let mut app = App::default();
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    max_members: Some(1),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        for member in ["member1", "member2", "member3", "member4"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        }
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap_err();
+
+        let members: MembersListResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MembersList {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(1, members.members.len());
+
+        let waitlist: WaitlistResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Waitlist {
+                    start_after: Some("member2".to_owned()),
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![Addr::unchecked("member3"), Addr::unchecked("member4")],
+            waitlist.waitlist
+        );
+
+        app.execute_contract(
+            Addr::unchecked("member3"),
+            manager.clone(),
+            &ExecMsg::LeaveWaitlist {},
+            &[],
+        )
+        .unwrap();
+
+        let position: WaitlistPositionResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::WaitlistPosition {
+                    addr: "member4".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(Some(2), position.position);
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        // The first waitlisted address is admitted in place of the leaving member
+        let _: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member2".to_owned(),
+                },
+            )
+            .unwrap();
+
+        let position: WaitlistPositionResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::WaitlistPosition {
+                    addr: "member4".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(Some(1), position.position);
+
+        let position: WaitlistPositionResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::WaitlistPosition {
+                    addr: "member2".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(None, position.position);
+    }
+
+    #[test]
+    fn invites() {
+        use sha2::{Digest, Sha256};
+
+        let mut app = App::default();
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    invite_only: true,
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let single_use = hex::encode(Sha256::digest(b"single-use"));
+        let expiring = hex::encode(Sha256::digest(b"expiring"));
+        let expires = app.block_info().time.plus_seconds(10);
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::AddInvite {
+                hash: single_use.clone(),
+                expires: None,
+                uses: Some(1),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::AddInvite {
+                hash: single_use.clone(),
+                expires: None,
+                uses: Some(1),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::AddInvite {
+                hash: expiring.clone(),
+                expires: Some(expires),
+                uses: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let mut expected = vec![
+            InviteResp {
+                hash: single_use,
+                expires: None,
+                uses_left: Some(1),
+            },
+            InviteResp {
+                hash: expiring.clone(),
+                expires: Some(expires),
+                uses_left: None,
+            },
+        ];
+        expected.sort_by(|a, b| a.hash.cmp(&b.hash));
+
+        let invites: InvitesResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Invites {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(expected, invites.invites);
+
+        for invite in [None, Some("wrong".to_owned())] {
+            app.execute_contract(
+                Addr::unchecked("member1"),
+                manager.clone(),
+                &ExecMsg::Join { invite },
+                &[],
+            )
+            .unwrap_err();
+        }
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Join {
+                invite: Some("single-use".to_owned()),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // Invite is used up
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Join {
+                invite: Some("single-use".to_owned()),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Join {
+                invite: Some("expiring".to_owned()),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.update_block(|block| block.time = expires);
+
+        app.execute_contract(
+            Addr::unchecked("member3"),
+            manager.clone(),
+            &ExecMsg::Join {
+                invite: Some("expiring".to_owned()),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        let invites: InvitesResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::Invites {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![InviteResp {
+                hash: expiring,
+                expires: Some(expires),
+                uses_left: None,
+            }],
+            invites.invites
+        );
+    }
+
+    #[test]
+    fn epochs() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(200, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    epoch_length: Some(100),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+        let start = app.block_info().time;
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // Donation waits for the epoch end
+        let epoch: EpochResp = app
+            .wrap()
+            .query_wasm_smart(manager.clone(), &QueryMsg::Epoch {})
+            .unwrap();
+        assert_eq!(
+            EpochResp {
+                id: 0,
+                start,
+                end: start.plus_seconds(100),
+                funds: coins(60, "utgd"),
+            },
+            epoch
+        );
+
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = start.plus_seconds(100);
+        });
+        let snapshot_height = app.block_info().height;
+
+        // Donators gained after the epoch end do not count for the closed epoch
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[1].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // Members cannot leave before their share of the closed epochs is distributed
+        let err = app
+            .execute_contract(
+                Addr::unchecked("member2"),
+                manager.clone(),
+                &ExecMsg::Leave {},
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            StdError::generic_err(
+                "Closed epochs have to be distributed with `DistributeEpochs {}` first"
+            ),
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            manager.clone(),
+            &ExecMsg::DistributeEpochs { limit: None },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            100
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[1], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            40
+        );
+
+        let history: EpochHistoryResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::EpochHistory {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![ClosedEpochResp {
+                id: 0,
+                start,
+                end: start.plus_seconds(100),
+                snapshot_height,
+                funds: coins(60, "utgd"),
+                distributed: true,
+            }],
+            history.epochs
+        );
+
+        let epoch: EpochResp = app
+            .wrap()
+            .query_wasm_smart(manager, &QueryMsg::Epoch {})
+            .unwrap();
+        assert_eq!(1, epoch.id);
+        assert_eq!(coins(60, "utgd"), epoch.funds);
+    }
+
+    #[test]
+    fn decayed_weights() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(200, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    weight_half_life: Some(100),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+        let start = app.block_info().time;
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = start.plus_seconds(100);
+        });
+
+        // After a half-life the first donator counts for half of the fresh one
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[1].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            120
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[1], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            80
+        );
+
+        // Donators counts are not decayed
+        let total: TotalWeightResponse = app
+            .wrap()
+            .query_wasm_smart(manager, &QueryMsg::TotalWeight {})
+            .unwrap();
+        assert_eq!(2, total.weight);
+    }
+
+    #[test]
+    fn share_limits() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(200, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    max_share: Some(Decimal::percent(50)),
+                    min_share: Some(Decimal::percent(10)),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2", "member3"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // The only donated peer is capped, and members without donators split the excess
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            70
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[1], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            15
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[2], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            15
+        );
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[1].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // Every member gets the floor before the split by weights
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            97
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[1], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            82
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[2], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            21
+        );
+    }
+
+    #[test]
+    fn simulate_distribution() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(300, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[1].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        let simulation: SimulateDistributionResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::SimulateDistribution {
+                    funds: coins(100, "utgd"),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            SimulateDistributionResp {
+                amounts: vec![
+                    PeerAmount {
+                        peer: peers[0].clone(),
+                        amount: coins(66, "utgd"),
+                    },
+                    PeerAmount {
+                        peer: peers[1].clone(),
+                        amount: coins(33, "utgd"),
+                    },
+                ],
+                leftover: coins(1, "utgd"),
+            },
+            simulation
+        );
+
+        let share: ExpectedShareResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::ExpectedShare {
+                    peer: peers[0].to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(Decimal::from_ratio(2u128, 3u128), share.share);
+
+        let err = app
+            .wrap()
+            .query_wasm_smart::<ExpectedShareResp>(
+                manager,
+                &QueryMsg::ExpectedShare {
+                    peer: "member1".to_owned(),
+                },
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("No such member"));
+    }
+
+    #[test]
+    fn distribution_history() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(200, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[1].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        let block = app.block_info();
+        let distributions: DistributionsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Distributions {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![
+                DistributionResp {
+                    id: 1,
+                    height: block.height,
+                    time: block.time,
+                    sender: peers[0].clone(),
+                    amounts: vec![PeerAmount {
+                        peer: peers[0].clone(),
+                        amount: coins(60, "utgd"),
+                    }],
+                    total_weight: Uint128::new(1),
+                    directed: false,
+                },
+                DistributionResp {
+                    id: 2,
+                    height: block.height,
+                    time: block.time,
+                    sender: peers[1].clone(),
+                    amounts: vec![
+                        PeerAmount {
+                            peer: peers[0].clone(),
+                            amount: coins(30, "utgd"),
+                        },
+                        PeerAmount {
+                            peer: peers[1].clone(),
+                            amount: coins(30, "utgd"),
+                        },
+                    ],
+                    total_weight: Uint128::new(2),
+                    directed: false,
+                },
+            ],
+            distributions.distributions
+        );
+
+        let distribution: DistributionResp = app
+            .wrap()
+            .query_wasm_smart(manager.clone(), &QueryMsg::Distribution { id: 2 })
+            .unwrap();
+        assert_eq!(distributions.distributions[1], distribution);
+
+        let page: DistributionsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Distributions {
+                    start_after: Some(1),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(vec![distribution], page.distributions);
+
+        let err = app
+            .wrap()
+            .query_wasm_smart::<DistributionResp>(
+                manager.clone(),
+                &QueryMsg::Distribution { id: 3 },
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("No such distribution"));
+
+        let earnings: PeerEarningsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::PeerEarnings {
+                    peer: peers[0].to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(coins(90, "utgd"), earnings.earned);
+
+        let earnings: PeerEarningsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::PeerEarnings {
+                    peer: peers[1].to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(coins(30, "utgd"), earnings.earned);
+    }
+
+    #[test]
+    fn members_stats() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(200, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+        let start = app.block_info().time;
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        let stats: MembersStatsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MembersStats {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![
+                MemberStats {
+                    addr: Addr::unchecked("member1"),
+                    peer_addr: peers[0].clone(),
+                    donators: 1,
+                    weight: Uint128::new(1),
+                    balance: coins(100, "utgd"),
+                    earned: coins(60, "utgd"),
+                    joined_at: Some(start),
+                },
+                MemberStats {
+                    addr: Addr::unchecked("member2"),
+                    peer_addr: peers[1].clone(),
+                    donators: 0,
+                    weight: Uint128::zero(),
+                    balance: vec![],
+                    earned: vec![],
+                    joined_at: Some(start),
+                },
+            ],
+            stats.members
+        );
+
+        let page: MembersStatsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MembersStats {
+                    start_after: Some(peers[0].to_string()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(stats.members[1..], page.members);
+    }
+
+    #[test]
+    fn top_peers() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(300, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    weight_half_life: Some(100),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+        let start = app.block_info().time;
+
+        let mut peers = vec![];
+        for member in ["member1", "member2", "member3"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        let top: TopPeersResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::TopPeers {
+                    limit: None,
+                    start_after: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![peers[0].clone(), peers[2].clone(), peers[1].clone()],
+            top.peers
+                .into_iter()
+                .map(|peer| peer.peer)
+                .collect::<Vec<_>>()
+        );
+
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = start.plus_seconds(150);
+        });
+
+        // A single fresh donator outweighs two donators from one and a half half-lives ago
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[1].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        let top: TopPeersResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::TopPeers {
+                    limit: Some(2),
+                    start_after: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![
+                RankedPeer {
+                    peer: peers[1].clone(),
+                    owner: Addr::unchecked("member2"),
+                    weight: Decimal::one().numerator(),
+                },
+                RankedPeer {
+                    peer: peers[0].clone(),
+                    owner: Addr::unchecked("member1"),
+                    weight: (Decimal::percent(200)
+                        * Decimal::percent(50)
+                        * Decimal::percent(50).sqrt())
+                    .numerator(),
+                },
+            ],
+            top.peers
+        );
+
+        let page: TopPeersResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::TopPeers {
+                    limit: None,
+                    start_after: Some(peers[0].to_string()),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![RankedPeer {
+                peer: peers[2].clone(),
+                owner: Addr::unchecked("member3"),
+                weight: Uint128::zero(),
+            }],
+            page.peers
+        );
+    }
+
+    #[test]
+    fn profiles() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(200, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap();
+
+        let peer: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member1".to_owned(),
+                },
+            )
+            .unwrap();
+        let peer = peer.addr;
+
+        let profile = Profile {
+            name: "Member One".to_owned(),
+            description: "Makes things".to_owned(),
+            avatar_url: Some("https://example.com/avatar.png".to_owned()),
+            links: vec!["https://example.com".to_owned()],
+            tags: vec!["art".to_owned(), "music".to_owned()],
+        };
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("member2"),
+                peer.clone(),
+                &PeerExec::SetProfile {
+                    profile: profile.clone(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            StdError::generic_err("Unauthorized"),
+            err.downcast().unwrap()
+        );
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("member1"),
+                peer.clone(),
+                &PeerExec::SetProfile {
+                    profile: Profile {
+                        tags: vec!["Art".to_owned()],
+                        ..profile.clone()
+                    },
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            StdError::generic_err("Invalid profile tag: Art"),
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            peer.clone(),
+            &PeerExec::SetProfile {
+                profile: profile.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let resp: ProfileResp = app
+            .wrap()
+            .query_wasm_smart(peer.clone(), &PeerQuery::Profile {})
+            .unwrap();
+        assert_eq!(Some(profile), resp.profile);
+
+        let members: MembersListResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::MembersList {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![Member {
+                addr: Addr::unchecked("member1"),
+                peer_addr: peer,
+                expires: None,
+                name: Some("Member One".to_owned()),
+                tags: vec!["art".to_owned(), "music".to_owned()],
+            }],
+            members.members
+        );
+
+        // Only member peers mirror profiles
+        let err = app
+            .execute_contract(
+                Addr::unchecked("member1"),
+                manager,
+                &ExecMsg::UpdateProfile {
+                    name: "Impostor".to_owned(),
+                    tags: vec![],
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            StdError::generic_err("Unauthorized"),
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn member_search() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(200, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2", "member3"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        let profiles = [
+            ("Alice", vec!["art", "music"]),
+            ("alfred", vec!["music"]),
+            ("Bob", vec!["art"]),
+        ];
+        for (idx, (name, tags)) in profiles.into_iter().enumerate() {
+            app.execute_contract(
+                Addr::unchecked(format!("member{}", idx + 1)),
+                peers[idx].clone(),
+                &PeerExec::SetProfile {
+                    profile: Profile {
+                        name: name.to_owned(),
+                        tags: tags.into_iter().map(str::to_owned).collect(),
+                        ..Profile::default()
+                    },
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let by_tag = |app: &App, tag: &str, start_after: Option<&str>| -> Vec<Addr> {
+            let members: MembersListResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MembersByTag {
+                        tag: tag.to_owned(),
+                        start_after: start_after.map(str::to_owned),
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            members
+                .members
+                .into_iter()
+                .map(|member| member.addr)
+                .collect()
+        };
+
+        let by_name = |app: &App, prefix: &str| -> Vec<Addr> {
+            let members: MembersListResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MembersByNamePrefix {
+                        prefix: prefix.to_owned(),
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            members
+                .members
+                .into_iter()
+                .map(|member| member.addr)
+                .collect()
+        };
+
+        assert_eq!(
+            vec![Addr::unchecked("member1"), Addr::unchecked("member2")],
+            by_tag(&app, "music", None)
+        );
+        assert_eq!(
+            vec![Addr::unchecked("member3")],
+            by_tag(&app, "art", Some("member1"))
+        );
+        assert_eq!(
+            vec![Addr::unchecked("member2"), Addr::unchecked("member1")],
+            by_name(&app, "AL")
+        );
+
+        // Renaming moves the member in the name index
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            peers[1].clone(),
+            &PeerExec::SetProfile {
+                profile: Profile {
+                    name: "Zed".to_owned(),
+                    ..Profile::default()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(vec![Addr::unchecked("member1")], by_name(&app, "al"));
+        assert_eq!(
+            vec![Addr::unchecked("member1")],
+            by_tag(&app, "music", None)
+        );
+
+        // Leaving removes the member from the indexes
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Leave {},
+            &[],
+        )
+        .unwrap();
+
+        assert!(by_name(&app, "al").is_empty());
+        assert_eq!(vec![Addr::unchecked("member3")], by_tag(&app, "art", None));
+    }
+
+    #[test]
+    fn directed_donations() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(301, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2", "member3"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("donator"),
+                manager.clone(),
+                &ExecMsg::DonateTo {
+                    peers: vec![
+                        (peers[0].to_string(), Decimal::percent(25)),
+                        (peers[2].to_string(), Decimal::percent(65)),
+                    ],
+                    count_as_donator: true,
+                },
+                &coins(301, "utgd"),
+            )
+            .unwrap_err();
+        assert_eq!(
+            StdError::generic_err("Fractions must sum up to one"),
+            err.downcast().unwrap()
+        );
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("donator"),
+                manager.clone(),
+                &ExecMsg::DonateTo {
+                    peers: vec![("member1".to_owned(), Decimal::one())],
+                    count_as_donator: true,
+                },
+                &coins(301, "utgd"),
+            )
+            .unwrap_err();
+        assert_eq!(
+            StdError::generic_err("Not a member peer: member1"),
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            manager.clone(),
+            &ExecMsg::DonateTo {
+                peers: vec![
+                    (peers[0].to_string(), Decimal::percent(25)),
+                    (peers[2].to_string(), Decimal::percent(75)),
+                ],
+                count_as_donator: false,
+            },
+            &coins(301, "utgd"),
+        )
+        .unwrap();
+
+        // The last peer takes the rounding dust
+        for (peer, balance) in [(&peers[0], 75), (&peers[1], 0), (&peers[2], 226)] {
+            assert_eq!(
+                app.wrap()
+                    .query_balance(peer, "utgd")
+                    .unwrap()
+                    .amount
+                    .u128(),
+                balance
+            );
+        }
+
+        // Directed donations are part of the distribution history
+        let distributions: DistributionsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Distributions {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let block = app.block_info();
+        assert_eq!(
+            vec![DistributionResp {
+                id: 1,
+                height: block.height,
+                time: block.time,
+                sender: Addr::unchecked("donator"),
+                amounts: vec![
+                    PeerAmount {
+                        peer: peers[0].clone(),
+                        amount: coins(75, "utgd"),
+                    },
+                    PeerAmount {
+                        peer: peers[2].clone(),
+                        amount: coins(226, "utgd"),
+                    },
+                ],
+                total_weight: Uint128::zero(),
+                directed: true,
+            }],
+            distributions.distributions
+        );
+
+        let earnings: PeerEarningsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::PeerEarnings {
+                    peer: peers[2].to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(coins(226, "utgd"), earnings.earned);
+
+        // Plain directed funds do not count as donations to the peers
+        for peer in [&peers[0], &peers[2]] {
+            let resp: DonatorsResp = app
+                .wrap()
+                .query_wasm_smart(peer, &PeerQuery::Donators {})
+                .unwrap();
+            assert_eq!(0, resp.donators);
+        }
+
+        let total: TotalWeightResponse = app
+            .wrap()
+            .query_wasm_smart(manager, &QueryMsg::TotalWeight {})
+            .unwrap();
+        assert_eq!(0, total.weight);
+    }
+
+    #[test]
+    fn counted_directed_donations() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(100, "utgd"))
+                .unwrap();
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("member1"), coins(150, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[1].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // Directing own funds to own peer costs the collective part like any donation
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::DonateTo {
+                peers: vec![(peers[0].to_string(), Decimal::one())],
+                count_as_donator: true,
+            },
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        for (peer, balance) in [(&peers[0], 70), (&peers[1], 130)] {
+            assert_eq!(
+                app.wrap()
+                    .query_balance(peer, "utgd")
+                    .unwrap()
+                    .amount
+                    .u128(),
+                balance
+            );
+        }
+
+        // The directed part kept by the peer and the distributed collective part are recorded
+        // separately, adding up to the donation
+        let distributions: DistributionsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Distributions {
+                    start_after: Some(1),
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let recorded: Vec<_> = distributions
+            .distributions
+            .iter()
+            .map(|distribution| {
+                let amounts: Vec<_> = distribution
+                    .amounts
+                    .iter()
+                    .map(|amount| (amount.peer.clone(), amount.amount[0].amount.u128()))
+                    .collect();
+                (distribution.directed, amounts)
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (true, vec![(peers[0].clone(), 40)]),
+                (false, vec![(peers[0].clone(), 30), (peers[1].clone(), 30)]),
+            ],
+            recorded
+        );
+        let total: u128 = recorded
+            .iter()
+            .flat_map(|(_, amounts)| amounts.iter().map(|(_, amount)| amount))
+            .sum();
+        assert_eq!(100, total);
+
+        // Only the peer receiving at least the incremental donation gains a donator
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::DonateTo {
+                peers: vec![
+                    (peers[0].to_string(), Decimal::percent(50)),
+                    (peers[1].to_string(), Decimal::percent(50)),
+                ],
+                count_as_donator: true,
+            },
+            &coins(50, "utgd"),
+        )
+        .unwrap();
+
+        for (peer, donators) in [(&peers[0], 1), (&peers[1], 1)] {
+            let resp: DonatorsResp = app
+                .wrap()
+                .query_wasm_smart(peer, &PeerQuery::Donators {})
+                .unwrap();
+            assert_eq!(donators, resp.donators);
+        }
+
+        // The collective parts coming back are distributed, nothing is left reserved
+        assert_eq!(
+            app.wrap()
+                .query_balance(&manager, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            0
+        );
+
+        let total: TotalWeightResponse = app
+            .wrap()
+            .query_wasm_smart(manager, &QueryMsg::TotalWeight {})
+            .unwrap();
+        assert_eq!(2, total.weight);
+    }
+
+    #[test]
+    fn sponsors() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(100, "utgd"))
+                .unwrap();
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked("brand1"),
+                    vec![coin(150, "utgd"), coin(100, "ustar")],
+                )
+                .unwrap();
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("brand2"), coins(300, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("brand1"),
+            manager.clone(),
+            &ExecMsg::Sponsor {
+                name: "Brand One".to_owned(),
+                message: "Hello".to_owned(),
+            },
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // Sponsorships are distributed like donations
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            200
+        );
+
+        app.execute_contract(
+            Addr::unchecked("brand2"),
+            manager.clone(),
+            &ExecMsg::Sponsor {
+                name: "Brand Two".to_owned(),
+                message: "Keep going".to_owned(),
+            },
+            &coins(300, "utgd"),
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("brand1"),
+            manager.clone(),
+            &ExecMsg::Sponsor {
+                name: "Brand One".to_owned(),
+                message: "Hello again".to_owned(),
+            },
+            &coins(50, "utgd"),
+        )
+        .unwrap();
+
+        let sponsors: SponsorsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Sponsors {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![
+                SponsorResp {
+                    addr: Addr::unchecked("brand2"),
+                    name: "Brand Two".to_owned(),
+                    message: "Keep going".to_owned(),
+                    total: coins(300, "utgd"),
+                },
+                SponsorResp {
+                    addr: Addr::unchecked("brand1"),
+                    name: "Brand One".to_owned(),
+                    message: "Hello again".to_owned(),
+                    total: coins(150, "utgd"),
+                },
+            ],
+            sponsors.sponsors
+        );
+
+        let page: SponsorsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager.clone(),
+                &QueryMsg::Sponsors {
+                    start_after: Some("brand2".to_owned()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(sponsors.sponsors[1..], page.sponsors);
+
+        // Changing the pool denom does not duplicate the ranking of existing sponsors
+        app.execute_contract(
+            Addr::unchecked("admin"),
+            manager.clone(),
+            &ExecMsg::UpdateConfig {
+                collective_ratio: None,
+                incremental_donation: Some(coin(100, "ustar")),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("brand1"),
+            manager.clone(),
+            &ExecMsg::Sponsor {
+                name: "Brand One".to_owned(),
+                message: "New currency".to_owned(),
+            },
+            &coins(100, "ustar"),
+        )
+        .unwrap();
+
+        let sponsors: SponsorsResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::Sponsors {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            vec![
+                SponsorResp {
+                    addr: Addr::unchecked("brand2"),
+                    name: "Brand Two".to_owned(),
+                    message: "Keep going".to_owned(),
+                    total: coins(300, "utgd"),
+                },
+                SponsorResp {
+                    addr: Addr::unchecked("brand1"),
+                    name: "Brand One".to_owned(),
+                    message: "New currency".to_owned(),
+                    total: vec![coin(150, "utgd"), coin(100, "ustar")],
+                },
+            ],
+            sponsors.sponsors
+        );
+    }
+
+    #[test]
+    fn governance_weights_from_proposal_creation() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(300, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    governance: Some(GovernanceConfig {
+                        voting_period: 100,
+                        quorum: Decimal::percent(50),
+                        threshold: Decimal::percent(50),
+                        voting_weight: VotingWeight::Donators,
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        // Voting weights are taken from the block before the proposal creation
+        app.update_block(|block| block.height += 1);
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Propose {
+                title: "Lower collective ratio".to_owned(),
+                description: "Peers should keep more of their donations".to_owned(),
+                action: ProposalAction::UpdateConfig {
+                    collective_ratio: Some(Decimal::percent(20)),
+                    incremental_donation: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+        // Members joining after the proposal creation cannot vote on it
+        app.execute_contract(
+            Addr::unchecked("member3"),
+            manager.clone(),
+            &ExecMsg::Join { invite: None },
+            &[],
+        )
+        .unwrap();
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("member3"),
+                manager.clone(),
+                &ExecMsg::Vote {
+                    proposal_id: 1,
+                    vote: VoteOption::No,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            StdError::generic_err("Only members at the proposal creation can vote"),
+            err.downcast().unwrap()
+        );
+
+        // Donators gained after the proposal creation do not count
+        for _ in 0..2 {
+            app.execute_contract(
+                Addr::unchecked("donator"),
+                peers[1].clone(),
+                &PeerExec::Donate {},
+                &coins(100, "utgd"),
+            )
+            .unwrap();
+        }
+
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Vote {
+                proposal_id: 1,
+                vote: VoteOption::No,
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("member1"),
+            manager.clone(),
+            &ExecMsg::Vote {
+                proposal_id: 1,
+                vote: VoteOption::Yes,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let proposal: ProposalResp = app
+            .wrap()
+            .query_wasm_smart(manager, &QueryMsg::Proposal { proposal_id: 1 })
+            .unwrap();
+        assert_eq!(ProposalStatus::Passed, proposal.status);
+        assert_eq!(
+            (1, 1, 0),
+            (proposal.total_weight, proposal.yes, proposal.no)
+        );
+    }
+
+    #[test]
+    fn epochs_with_membership_terms() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(300, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    epoch_length: Some(100),
+                    membership_term: Some(MembershipTerm {
+                        duration: 150,
+                        renewal_fee: None,
+                    }),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+        let start = app.block_info().time;
+
+        let mut peers = vec![];
+        for member in ["member1", "member2"] {
+            app.execute_contract(
+                Addr::unchecked(member),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+
+            let peer: MemberPeerAddrResp = app
+                .wrap()
+                .query_wasm_smart(
+                    manager.clone(),
+                    &QueryMsg::MemberPeerAddr {
+                        addr: member.to_owned(),
+                    },
+                )
+                .unwrap();
+            peers.push(peer.addr);
+        }
+
+        for peer in &peers {
+            app.execute_contract(
+                Addr::unchecked("donator"),
+                peer.clone(),
+                &PeerExec::Donate {},
+                &coins(100, "utgd"),
+            )
+            .unwrap();
+        }
+
+        // Both memberships expire during the second epoch
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = start.plus_seconds(160);
+        });
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peers[0].clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = start.plus_seconds(210);
+        });
+
+        // Renewal does not bring back the share of the epoch the membership was expired in
+        app.execute_contract(
+            Addr::unchecked("member2"),
+            manager.clone(),
+            &ExecMsg::Renew {},
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            manager,
+            &ExecMsg::DistributeEpochs { limit: None },
+            &[],
+        )
+        .unwrap();
+
+        // The first epoch is split between both members active at its end, even though the
+        // first one is expired by now. Nobody was active at the end of the second epoch.
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[0], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            140
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peers[1], "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            100
+        );
+    }
+
+    #[test]
+    fn failing_pool_hook_does_not_revert() {
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("donator"), coins(100, "utgd"))
+                .unwrap();
+        });
+        let peer_code_id = app.store_code(peer());
+        let manager_code_id = app.store_code(manager());
+        let hook_code_id = app.store_code(pool_hook());
+        let failing_hook_code_id = app.store_code(failing_pool_hook());
+
+        let manager = app
+            .instantiate_contract(
+                manager_code_id,
+                Addr::unchecked("admin"),
+                &InstantiateMsg {
+                    peer_code_id,
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    admin: Some("admin".to_owned()),
+                    ..InstantiateMsg::default()
+                },
+                &[],
+                "manager",
+                None,
+            )
+            .unwrap();
+
+        let mut hooks = vec![];
+        for code_id in [failing_hook_code_id, hook_code_id] {
+            let hook = app
+                .instantiate_contract(
+                    code_id,
+                    Addr::unchecked("admin"),
+                    &Empty {},
+                    &[],
+                    "hook",
+                    None,
+                )
+                .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked("admin"),
+                manager.clone(),
+                &ExecMsg::AddEventHook {
+                    addr: hook.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+            hooks.push(hook);
+        }
+
+        let resp = app
+            .execute_contract(
+                Addr::unchecked("member"),
+                manager.clone(),
+                &ExecMsg::Join { invite: None },
+                &[],
+            )
+            .unwrap();
+        assert!(resp.events.iter().any(|event| event
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "event_hook_error")));
+
+        let peer: MemberPeerAddrResp = app
+            .wrap()
+            .query_wasm_smart(
+                manager,
+                &QueryMsg::MemberPeerAddr {
+                    addr: "member".to_owned(),
+                },
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("donator"),
+            peer.addr.clone(),
+            &PeerExec::Donate {},
+            &coins(100, "utgd"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap()
+                .query_balance(&peer.addr, "utgd")
+                .unwrap()
+                .amount
+                .u128(),
+            100
+        );
+
+        // Other hooks are still notified
+        let events: Vec<PoolHookMsg> = app.wrap().query_wasm_smart(&hooks[1], &Empty {}).unwrap();
+        assert_eq!(
+            vec![
+                PoolHookMsg::MemberJoined {
+                    member: Addr::unchecked("member"),
+                    peer: peer.addr.clone(),
+                },
+                PoolHookMsg::Distribution {
+                    sender: peer.addr.clone(),
+                    amounts: vec![PeerAmount {
+                        peer: peer.addr,
+                        amount: coins(60, "utgd"),
+                    }],
+                },
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn event_hooks_gas_limit() {
+        // Multi-test does not meter gas, so the limit is checked on the notifications directly
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            InstantiateMsg {
+                peer_code_id: 1,
+                incremental_donation: coin(100, "utgd"),
+                collective_ratio: Decimal::percent(60),
+                admin: Some("admin".to_owned()),
+                ..InstantiateMsg::default()
+            },
+        )
+        .unwrap();
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecMsg::AddEventHook {
+                addr: "hook".to_owned(),
+            },
+        )
+        .unwrap();
+
+        crate::state::MEMBERS
+            .save(
+                deps.as_mut().storage,
+                Addr::unchecked("peer"),
+                &Addr::unchecked("member"),
+            )
+            .unwrap();
+
+        let resp = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("donator", &coins(100, "utgd")),
+            ExecMsg::DonateTo {
+                peers: vec![("peer".to_owned(), Decimal::one())],
+                count_as_donator: false,
+            },
+        )
+        .unwrap();
+
+        let hooks: Vec<_> = resp
+            .messages
+            .iter()
+            .filter(|msg| msg.id == contract::EVENT_HOOK_ID)
+            .collect();
+        assert_eq!(1, hooks.len());
+        assert_eq!(ReplyOn::Error, hooks[0].reply_on);
+        assert_eq!(Some(contract::EVENT_HOOK_GAS_LIMIT), hooks[0].gas_limit);
+    }
 }
@@ -1,20 +1,278 @@
-use cosmwasm_std::{Addr, Coin, Decimal};
+use cosmwasm_std::{Addr, Coin, Decimal, StdError, StdResult, Timestamp, Uint128};
+use donation_peer::msg::VestingSchedule;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct InstantiateMsg {
     pub peer_code_id: u64,
     pub incremental_donation: Coin,
     pub collective_ratio: Decimal,
+    /// Vesting schedule applied to every peer created by the manager
+    #[serde(default)]
+    pub vesting: Option<VestingSchedule>,
+    /// Address allowed to pause and unpause the system
+    #[serde(default)]
+    pub pauser: Option<String>,
+    /// Address allowed to manage the pool
+    #[serde(default)]
+    pub admin: Option<String>,
+    /// If set, only applicants approved by the admin or an existing member can join
+    #[serde(default)]
+    pub require_approval: bool,
+    /// If set, members can change the pool configuration through proposals
+    #[serde(default)]
+    pub governance: Option<GovernanceConfig>,
+    /// If set, leaving takes effect only after this many seconds since `Leave {}` was first
+    /// called. The member still receives distributions in the meantime.
+    #[serde(default)]
+    pub leave_notice_period: Option<u64>,
+    /// Funds which have to be sent with `Join {}`
+    #[serde(default)]
+    pub join_requirement: Option<JoinRequirement>,
+    /// If set, membership expires unless it is renewed
+    #[serde(default)]
+    pub membership_term: Option<MembershipTerm>,
+    /// If set, joining a full pool puts the sender on the waitlist
+    #[serde(default)]
+    pub max_members: Option<u32>,
+    /// If set, `Join {}` requires an invite code registered by the admin
+    #[serde(default)]
+    pub invite_only: bool,
+    /// If set, donations are collected in epochs of this many seconds. Member weights are
+    /// snapshotted when the epoch closes, and the collected funds are distributed by
+    /// `DistributeEpochs {}` using the snapshot.
+    #[serde(default)]
+    pub epoch_length: Option<u64>,
+    /// If set, every donator increment of a peer decays with this half-life in seconds, and
+    /// distributions use the decayed weights instead of plain donators counts
+    #[serde(default)]
+    pub weight_half_life: Option<u64>,
+    /// The most a single peer can take of a distribution. The excess is redistributed between
+    /// the other peers. Never lower than an equal split between all members.
+    #[serde(default)]
+    pub max_share: Option<Decimal>,
+    /// The least every member takes of a distribution, regardless of its weight. Never higher
+    /// than an equal split between all members.
+    #[serde(default)]
+    pub min_share: Option<Decimal>,
+}
+
+/// Members join for `duration` seconds, and `Renew {}` extends the membership by another
+/// `duration`, paying the `renewal_fee` into the pool. Expired members take no part in
+/// distributions, and can be pruned by anyone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MembershipTerm {
+    pub duration: u64,
+    pub renewal_fee: Option<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinRequirement {
+    /// Non-refundable fee, sent to the `treasury`, or left in the pool to be distributed if
+    /// there is no treasury
+    Fee {
+        amount: Coin,
+        treasury: Option<String>,
+    },
+    /// Stake locked on the manager while the member is in the pool, and returned when it
+    /// leaves or its membership is pruned. It can be slashed by the admin, and it is lost
+    /// entirely when the member is expelled.
+    Stake { amount: Coin },
+}
+
+impl JoinRequirement {
+    pub fn amount(&self) -> &Coin {
+        match self {
+            Self::Fee { amount, .. } => amount,
+            Self::Stake { amount } => amount,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingWeight {
+    /// Every member has a single vote
+    Member,
+    /// Member votes are weighted by the donators count of their peers
+    Donators,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct GovernanceConfig {
+    /// Time in seconds for which the proposal is open for voting
+    pub voting_period: u64,
+    /// Part of the total weight which has to vote for the proposal to be valid
+    pub quorum: Decimal,
+    /// Part of the yes and no votes weight which has to vote yes for the proposal to pass
+    pub threshold: Decimal,
+    pub voting_weight: VotingWeight,
+}
+
+impl GovernanceConfig {
+    pub fn validate(&self) -> StdResult<()> {
+        if self.voting_period == 0 {
+            return Err(StdError::generic_err("Voting period must be non-zero"));
+        }
+
+        if self.quorum.is_zero() || self.quorum > Decimal::one() {
+            return Err(StdError::generic_err("Quorum must be in (0, 1] range"));
+        }
+
+        if self.threshold.is_zero() || self.threshold > Decimal::one() {
+            return Err(StdError::generic_err("Threshold must be in (0, 1] range"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalAction {
+    UpdateConfig {
+        collective_ratio: Option<Decimal>,
+        incremental_donation: Option<Coin>,
+    },
+    RemoveMember {
+        owner: String,
+        #[serde(default)]
+        reason: String,
+    },
+    UpgradePeers {
+        code_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteOption {
+    Yes,
+    No,
+    Abstain,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Open,
+    Passed,
+    Rejected,
+    Executed,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecMsg {
-    Join {},
+    Join {
+        /// Invite code, required in the invite-only mode
+        invite: Option<String>,
+    },
     Leave {},
+    Renew {},
+    /// Removes up to `limit` expired members
+    Prune {
+        limit: Option<u32>,
+    },
+    LeaveWaitlist {},
+    /// Admin only - registers the invite code by its hex encoded sha256 hash. The invite is
+    /// valid until `expires`, and can be used `uses` times (unlimited if not set).
+    AddInvite {
+        hash: String,
+        expires: Option<Timestamp>,
+        uses: Option<u32>,
+    },
+    /// Admin only
+    RemoveInvite {
+        hash: String,
+    },
     Donate {},
+    /// Splits the attached funds between the chosen member peers by the given fractions,
+    /// summing up to one. With `count_as_donator`, the funds are donated through every peer
+    /// `Donate {}` instead, so the collective part is taken and split between all the members
+    /// as usual, and peers receiving at least the incremental donation gain a donator.
+    DonateTo {
+        peers: Vec<(String, Decimal)>,
+        #[serde(default)]
+        count_as_donator: bool,
+    },
+    /// Donates the attached funds like `Donate {}`, publicly attributed to the sponsor.
+    /// Totals are tracked per sponsor address - `name` and `message` are for display only,
+    /// every sponsorship replaces the previous ones.
+    Sponsor {
+        name: String,
+        message: String,
+    },
+    /// Member peer only - mirrors the name and tags of the peer profile
+    UpdateProfile {
+        name: String,
+        tags: Vec<String>,
+    },
+    /// Distributes up to `limit` closed epochs
+    DistributeEpochs {
+        limit: Option<u32>,
+    },
+    Pause {},
+    Unpause {},
+    Apply {
+        metadata: String,
+    },
+    Approve {
+        applicant: String,
+    },
+    Reject {
+        applicant: String,
+        reason: Option<String>,
+    },
+    UpdateConfig {
+        collective_ratio: Option<Decimal>,
+        incremental_donation: Option<Coin>,
+    },
+    UpgradePeers {
+        code_id: u64,
+    },
+    Propose {
+        title: String,
+        description: String,
+        action: ProposalAction,
+    },
+    Vote {
+        proposal_id: u64,
+        vote: VoteOption,
+    },
+    ExecuteProposal {
+        proposal_id: u64,
+    },
+    AddHook {
+        addr: String,
+    },
+    RemoveHook {
+        addr: String,
+    },
+    AddEventHook {
+        addr: String,
+    },
+    RemoveEventHook {
+        addr: String,
+    },
+    /// Admin only - removes the member from the pool and bans it from rejoining
+    RemoveMember {
+        owner: String,
+        reason: String,
+    },
+    /// Admin only - allows the removed member to join again
+    Unban {
+        owner: String,
+    },
+    /// Admin only - takes `amount` (everything if not set) of the member stake into the pool
+    Slash {
+        owner: String,
+        amount: Option<Uint128>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -28,6 +286,113 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u64>,
     },
+    Paused {},
+    Applications {
+        start_after: Option<String>,
+        limit: Option<u64>,
+    },
+    Proposal {
+        proposal_id: u64,
+    },
+    Proposals {
+        start_after: Option<u64>,
+        limit: Option<u64>,
+    },
+    Vote {
+        proposal_id: u64,
+        voter: String,
+    },
+    Votes {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u64>,
+    },
+    // cw4 group interface - the same as `cw4::Cw4QueryMsg`, answered with the `cw4` responses
+    Admin {},
+    TotalWeight {},
+    ListMembers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Member {
+        addr: String,
+        at_height: Option<u64>,
+    },
+    Hooks {},
+    EventHooks {},
+    PendingLeave {
+        addr: String,
+    },
+    RemovedMembers {
+        start_after: Option<String>,
+        limit: Option<u64>,
+    },
+    Stake {
+        addr: String,
+    },
+    Waitlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    WaitlistPosition {
+        addr: String,
+    },
+    Invites {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Epoch {},
+    EpochHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// How `funds` donated now would be split between the member peers
+    SimulateDistribution {
+        funds: Vec<Coin>,
+    },
+    /// Fraction of the donations the member `peer` currently receives
+    ExpectedShare {
+        peer: String,
+    },
+    Distributions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Distribution {
+        id: u64,
+    },
+    /// Everything the manager ever sent to the `peer`
+    PeerEarnings {
+        peer: String,
+    },
+    /// Members with their peers statistics, paginated by the peer address like `MembersList`
+    MembersStats {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Member peers from the highest weight down. `start_after` is the last peer of the
+    /// previous page.
+    TopPeers {
+        limit: Option<u32>,
+        start_after: Option<String>,
+    },
+    /// Members with the profile `tag`, paginated by the member address
+    MembersByTag {
+        tag: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Members with profile names starting with `prefix`, case insensitive, ordered by name
+    MembersByNamePrefix {
+        prefix: String,
+        limit: Option<u32>,
+    },
+    /// Sponsors from the highest total contributed in the incremental donation denom down.
+    /// `start_after` is the last sponsor of the previous page.
+    Sponsors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -35,6 +400,129 @@ pub enum QueryMsg {
 pub struct ConfigResp {
     pub incremental_donation: Coin,
     pub collective_ratio: Decimal,
+    pub vesting: Option<VestingSchedule>,
+    pub admin: Option<Addr>,
+    pub require_approval: bool,
+    pub governance: Option<GovernanceConfig>,
+    pub leave_notice_period: Option<u64>,
+    pub join_requirement: Option<JoinRequirement>,
+    pub membership_term: Option<MembershipTerm>,
+    pub max_members: Option<u32>,
+    pub invite_only: bool,
+    pub epoch_length: Option<u64>,
+    pub weight_half_life: Option<u64>,
+    pub max_share: Option<Decimal>,
+    pub min_share: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct EpochResp {
+    pub id: u64,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    /// Funds collected since the last closed epoch
+    pub funds: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ClosedEpochResp {
+    pub id: u64,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    /// Height at which member weights were snapshotted
+    pub snapshot_height: u64,
+    /// Funds to be distributed, or distributed if `distributed` is set
+    pub funds: Vec<Coin>,
+    pub distributed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct EpochHistoryResp {
+    pub epochs: Vec<ClosedEpochResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SimulateDistributionResp {
+    pub amounts: Vec<PeerAmount>,
+    /// Rounding dust staying on the manager until the next distribution
+    pub leftover: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ExpectedShareResp {
+    pub share: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DistributionResp {
+    pub id: u64,
+    pub height: u64,
+    pub time: Timestamp,
+    pub sender: Addr,
+    pub amounts: Vec<PeerAmount>,
+    pub total_weight: Uint128,
+    /// Split by the donor with `DonateTo`, `total_weight` is zero then
+    pub directed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DistributionsResp {
+    pub distributions: Vec<DistributionResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PeerEarningsResp {
+    pub earned: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct InviteResp {
+    pub hash: String,
+    pub expires: Option<Timestamp>,
+    /// `None` if the invite can be used any number of times
+    pub uses_left: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct InvitesResp {
+    pub invites: Vec<InviteResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct WaitlistResp {
+    /// Waiting addresses, in order of admission
+    pub waitlist: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct WaitlistPositionResp {
+    /// Position on the waitlist counting from 1, `None` if the address is not waiting
+    pub position: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct StakeResp {
+    pub stake: Option<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingLeaveResp {
+    /// Time since which the leave can be completed, `None` if the member is not leaving
+    pub leave_at: Option<Timestamp>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -48,6 +536,11 @@ pub struct MemberPeerAddrResp {
 pub struct Member {
     pub addr: Addr,
     pub peer_addr: Addr,
+    /// Set if the pool has membership terms
+    pub expires: Option<Timestamp>,
+    /// Set once the owner sets the peer profile
+    pub name: Option<String>,
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -55,3 +548,173 @@ pub struct Member {
 pub struct MembersListResp {
     pub members: Vec<Member>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MemberStats {
+    pub addr: Addr,
+    pub peer_addr: Addr,
+    pub donators: u64,
+    /// Weight used for distributions - the donators count, or the decayed weight atomics if
+    /// the pool has the weight half-life set
+    pub weight: Uint128,
+    /// Funds held by the peer, not withdrawn by its owner yet
+    pub balance: Vec<Coin>,
+    /// Everything the manager ever sent to the peer
+    pub earned: Vec<Coin>,
+    /// Not known for members which joined before join times were recorded
+    pub joined_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MembersStatsResp {
+    pub members: Vec<MemberStats>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct RankedPeer {
+    pub peer: Addr,
+    pub owner: Addr,
+    /// Weight used for distributions, like in `MemberStats`
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TopPeersResp {
+    pub peers: Vec<RankedPeer>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SponsorResp {
+    pub addr: Addr,
+    pub name: String,
+    pub message: String,
+    pub total: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SponsorsResp {
+    pub sponsors: Vec<SponsorResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PausedResp {
+    pub paused: bool,
+    pub pauser: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ApplicationResp {
+    pub applicant: Addr,
+    pub metadata: String,
+    pub applied_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ApplicationsResp {
+    pub applications: Vec<ApplicationResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct RemovedMemberResp {
+    pub addr: Addr,
+    pub peer: Addr,
+    pub reason: String,
+    pub removed_at: Timestamp,
+    pub banned: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct RemovedMembersResp {
+    pub members: Vec<RemovedMemberResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalResp {
+    pub id: u64,
+    pub proposer: Addr,
+    pub title: String,
+    pub description: String,
+    pub action: ProposalAction,
+    pub expires: Timestamp,
+    pub total_weight: u64,
+    pub yes: u64,
+    pub no: u64,
+    pub abstain: u64,
+    pub status: ProposalStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalsResp {
+    pub proposals: Vec<ProposalResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct VoteInfo {
+    pub voter: Addr,
+    pub vote: VoteOption,
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct VoteResp {
+    pub vote: Option<VoteInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct VotesResp {
+    pub votes: Vec<VoteInfo>,
+}
+
+// Pool events notification sent to the registered event hooks
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PeerAmount {
+    pub peer: Addr,
+    pub amount: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolHookMsg {
+    MemberJoined {
+        member: Addr,
+        peer: Addr,
+    },
+    MemberLeft {
+        member: Addr,
+        peer: Addr,
+    },
+    /// Final share of the undistributed manager funds sent to the leaving member peer
+    Settlement {
+        member: Addr,
+        peer: Addr,
+        amount: Vec<Coin>,
+    },
+    Distribution {
+        sender: Addr,
+        amounts: Vec<PeerAmount>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolHookExecuteMsg {
+    PoolHook(PoolHookMsg),
+}
@@ -1,5 +1,11 @@
-use cosmwasm_std::{Addr, Coin, Decimal};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Coin, Decimal, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotMap, Strategy};
+use donation_peer::msg::VestingSchedule;
+
+use crate::msg::{
+    GovernanceConfig, JoinRequirement, MembershipTerm, PeerAmount, ProposalAction, ProposalStatus,
+    VoteOption,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -8,11 +14,331 @@ pub struct Config {
     pub peer_code_id: u64,
     pub incremental_donation: Coin,
     pub collective_ratio: Decimal,
+    pub vesting: Option<VestingSchedule>,
+    pub pauser: Option<Addr>,
+    pub admin: Option<Addr>,
+    pub require_approval: bool,
+    pub governance: Option<GovernanceConfig>,
+    #[serde(default)]
+    pub leave_notice_period: Option<u64>,
+    #[serde(default)]
+    pub join_requirement: Option<JoinRequirement>,
+    #[serde(default)]
+    pub membership_term: Option<MembershipTerm>,
+    #[serde(default)]
+    pub max_members: Option<u32>,
+    #[serde(default)]
+    pub invite_only: bool,
+    #[serde(default)]
+    pub epoch_length: Option<u64>,
+    #[serde(default)]
+    pub weight_half_life: Option<u64>,
+    #[serde(default)]
+    pub max_share: Option<Decimal>,
+    #[serde(default)]
+    pub min_share: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Invite {
+    pub expires: Option<Timestamp>,
+    pub uses_left: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Application {
+    pub metadata: String,
+    pub applied_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Removal {
+    pub peer: Addr,
+    pub reason: String,
+    pub removed_at: Timestamp,
+    pub banned: bool,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
 // Maps `donations-peer` contract address to its owner address
-pub const MEMBERS: Map<Addr, Addr> = Map::new("members");
+pub const MEMBERS: Map<Addr, Addr> = Map::new("peers");
+
+// Number of entries in `MEMBERS`
+pub const MEMBER_COUNT: Item<u32> = Item::new("member_count");
+
+// Maps owner address to its `donation-peer` contract address
+pub const OWNERS: Map<&Addr, Addr> = Map::new("owners");
+
+// Donators count of every member peer by the owner address. Together with `TOTAL_WEIGHT` it
+// follows the `cw4-group` storage layout, so cw4 raw queries work against the manager.
+pub const MEMBER_WEIGHTS: SnapshotMap<&Addr, u64> = SnapshotMap::new(
+    "members",
+    "members__checkpoints",
+    "members__changelog",
+    Strategy::EveryBlock,
+);
+
+pub const TOTAL_WEIGHT: Item<u64> = Item::new("total");
+
+// Members ordered by their rank, which orders them the same way as their current weights
+pub const RANKED: Map<(u128, &Addr), ()> = Map::new("ranked");
+
+// Rank of every member by the owner address, to find it in `RANKED`
+pub const RANKS: Map<&Addr, u128> = Map::new("ranks");
+
+/// Sum of the member weight increments, each decayed since it happened. Only `score` as of
+/// `updated` is stored, the decay until now is applied lazily.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DecayedWeight {
+    pub score: Decimal,
+    pub updated: Timestamp,
+}
+
+// Decayed weights by owner address, present if the pool has the weight half-life set
+pub const DECAYED_WEIGHTS: SnapshotMap<&Addr, DecayedWeight> = SnapshotMap::new(
+    "decayed_weights",
+    "decayed_weights__checkpoints",
+    "decayed_weights__changelog",
+    Strategy::EveryBlock,
+);
+
+// Contracts notified about the membership changes with the cw4 `MemberChangedHook` message
+pub const HOOKS: Item<Vec<Addr>> = Item::new("cw4-hooks");
+
+// Contracts notified about joins, leaves and distributions with the `PoolHook` message
+pub const EVENT_HOOKS: Item<Vec<Addr>> = Item::new("event_hooks");
+
+// When set, joining and donations are rejected - both on the manager and on peers
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+// Pending membership applications by applicant address
+pub const APPLICATIONS: Map<&Addr, Application> = Map::new("applications");
+
+// Members which announced leaving, mapped to the time since the leave can be completed
+pub const LEAVING: Map<&Addr, Timestamp> = Map::new("leaving");
+
+// Members removed by the admin or governance, by the owner address. Banned ones cannot rejoin.
+pub const REMOVED: Map<&Addr, Removal> = Map::new("removed");
+
+// Membership expiration time by the owner address, present if the pool has membership terms.
+// Snapshotted, so epochs see expirations as of their end.
+pub const EXPIRES: SnapshotMap<&Addr, Timestamp> = SnapshotMap::new(
+    "expires",
+    "expires__checkpoints",
+    "expires__changelog",
+    Strategy::EveryBlock,
+);
+
+// Members ordered by the expiration time in nanoseconds, so pruning reads only expired ones
+pub const EXPIRING: Map<(u64, &Addr), ()> = Map::new("expiring");
+
+/// Part of the peer profile mirrored by the manager
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MemberProfile {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+// Profiles of the members by the owner address
+pub const PROFILES: Map<&Addr, MemberProfile> = Map::new("profiles");
+
+// Members by their profile tags
+pub const TAGGED: Map<(&str, &Addr), ()> = Map::new("tagged");
+
+// Members by their lowercase profile names, keyed with `name_index_key` so names sharing a
+// prefix are stored next to each other
+pub const NAMES: Map<&str, ()> = Map::new("names");
+
+const NAME_INDEX_SEPARATOR: char = '\u{0}';
+
+pub fn name_index_key(name: &str, owner: &Addr) -> String {
+    format!("{}{}{}", name.to_lowercase(), NAME_INDEX_SEPARATOR, owner)
+}
+
+/// Owner address from the `NAMES` key
+pub fn name_index_owner(key: &str) -> Option<Addr> {
+    key.rsplit_once(NAME_INDEX_SEPARATOR)
+        .map(|(_, owner)| Addr::unchecked(owner))
+}
+
+// Join time by the owner address
+pub const JOINED: Map<&Addr, Timestamp> = Map::new("joined");
+
+// Stakes locked by members and waitlisted addresses
+pub const STAKES: Map<&Addr, Coin> = Map::new("stakes");
+
+// Funds on the manager balance which are not part of the pool - they are never distributed
+pub const RESERVED: Item<Vec<Coin>> = Item::new("reserved");
+
+// Directed donations sent to member peer `Donate {}`, reserved until the peer forwards the
+// collective part back - they are already gone from the balance by then
+pub const DIRECTED: Map<&Addr, Vec<Coin>> = Map::new("directed");
+
+// Applicants allowed to join, mapped to the address which approved them
+pub const APPROVED: Map<&Addr, Addr> = Map::new("approved");
+
+// Invites by the hex encoded sha256 hash of the invite code
+pub const INVITES: Map<&str, Invite> = Map::new("invites");
+
+// Addresses waiting for a free slot in the full pool, by the ticket number. The lowest ticket
+// is admitted first.
+pub const WAITLIST: Map<u64, Addr> = Map::new("waitlist");
+
+// Waitlist ticket of every waiting address
+pub const WAITLIST_TICKETS: Map<&Addr, u64> = Map::new("waitlist_tickets");
+
+pub const NEXT_WAITLIST_TICKET: Item<u64> = Item::new("next_waitlist_ticket");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Proposal {
+    pub proposer: Addr,
+    pub title: String,
+    pub description: String,
+    pub action: ProposalAction,
+    pub expires: Timestamp,
+    // Voting weights are taken from `MEMBER_WEIGHTS` at this height
+    pub start_height: u64,
+    // Weight of all members at the proposal creation
+    pub total_weight: u64,
+    pub yes: u64,
+    pub no: u64,
+    pub abstain: u64,
+    pub executed: bool,
+}
+
+impl Proposal {
+    pub fn status(&self, governance: &GovernanceConfig, now: Timestamp) -> ProposalStatus {
+        if self.executed {
+            return ProposalStatus::Executed;
+        }
+
+        if self.total_weight == 0 {
+            return if now < self.expires {
+                ProposalStatus::Open
+            } else {
+                ProposalStatus::Rejected
+            };
+        }
+
+        let votes = self.yes + self.no + self.abstain;
+        let quorum_reached = Decimal::from_ratio(votes, self.total_weight) >= governance.quorum;
+
+        // Enough yes votes that remaining votes cannot change the result
+        if quorum_reached
+            && Decimal::from_ratio(self.yes, self.total_weight) >= governance.threshold
+        {
+            return ProposalStatus::Passed;
+        }
+
+        if now < self.expires {
+            return ProposalStatus::Open;
+        }
+
+        let decisive = self.yes + self.no;
+        if quorum_reached
+            && decisive > 0
+            && Decimal::from_ratio(self.yes, decisive) >= governance.threshold
+        {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Ballot {
+    pub vote: VoteOption,
+    pub weight: u64,
+}
+
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+
+// Votes by proposal id and voter address
+pub const VOTES: Map<(u64, &Addr), Ballot> = Map::new("votes");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct OpenEpoch {
+    pub id: u64,
+    pub start: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ClosedEpoch {
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub snapshot_height: u64,
+    pub funds: Vec<Coin>,
+    pub distributed: bool,
+}
+
+// Epoch collecting donations, present if the pool distributes in epochs
+pub const OPEN_EPOCH: Item<OpenEpoch> = Item::new("open_epoch");
+
+// Closed epochs by id. Funds of the undistributed ones are reserved on the manager.
+pub const EPOCHS: Map<u64, ClosedEpoch> = Map::new("epochs");
+
+// Id from which closed epochs are waiting for the distribution
+pub const DISTRIBUTION_CURSOR: Item<u64> = Item::new("distribution_cursor");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DistributionRecord {
+    pub height: u64,
+    pub time: Timestamp,
+    pub sender: Addr,
+    pub amounts: Vec<PeerAmount>,
+    pub total_weight: Uint128,
+    /// Split by the donor with `DonateTo`, not by the member weights
+    #[serde(default)]
+    pub directed: bool,
+}
+
+pub const DISTRIBUTION_COUNT: Item<u64> = Item::new("distribution_count");
+
+// Every distribution sent to the member peers by id
+pub const DISTRIBUTIONS: Map<u64, DistributionRecord> = Map::new("distributions");
+
+// Everything the manager ever sent to the peer, both in distributions and settlements
+pub const PEER_EARNINGS: Map<&Addr, Vec<Coin>> = Map::new("peer_earnings");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Sponsorship {
+    /// Name and message from the latest sponsorship, for display only
+    pub name: String,
+    pub message: String,
+    pub total: Vec<Coin>,
+    /// Key in `SPONSOR_RANKS` - total contributed in the pool denom as of the latest
+    /// sponsorship. Stored, so the entry can be found even after the denom changes.
+    pub rank: u128,
+}
+
+impl Sponsorship {
+    /// Total contributed in the pool `denom`, which orders sponsors
+    pub fn total_in(&self, denom: &str) -> u128 {
+        self.total
+            .iter()
+            .find(|coin| coin.denom == denom)
+            .map(|coin| coin.amount.u128())
+            .unwrap_or_default()
+    }
+}
+
+// Sponsorships by the sponsor address
+pub const SPONSORS: Map<&Addr, Sponsorship> = Map::new("sponsors");
 
-pub const PENDING_INSTANTIATION: Item<Addr> = Item::new("pending_instantiation");
+// Sponsors ordered by the total contributed in the incremental donation denom
+pub const SPONSOR_RANKS: Map<(u128, &Addr), ()> = Map::new("sponsor_ranks");
@@ -0,0 +1,32 @@
+use cosmwasm_std::Coin;
+
+/// Adds `coins` to `target`, merging amounts of the same denom
+pub fn add_coins(target: &mut Vec<Coin>, coins: &[Coin]) {
+    for coin in coins {
+        match target.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(c) => c.amount += coin.amount,
+            None => target.push(coin.clone()),
+        }
+    }
+
+    target.retain(|c| !c.amount.is_zero());
+}
+
+/// Subtracts `coins` from `target` per denom, going down to zero at most
+pub fn sub_coins_saturating(target: &[Coin], coins: &[Coin]) -> Vec<Coin> {
+    target
+        .iter()
+        .map(|coin| {
+            let sub = coins
+                .iter()
+                .find(|c| c.denom == coin.denom)
+                .map(|c| c.amount)
+                .unwrap_or_default();
+            Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount.saturating_sub(sub),
+            }
+        })
+        .filter(|c| !c.amount.is_zero())
+        .collect()
+}
@@ -1,5 +1,5 @@
-use crate::msg::{InstantiateMsg, ManagerExec};
-use crate::state::{State, OWNER, STATE};
+use crate::msg::{InstantiateMsg, ManagerExec, ManagerPausedResp, ManagerQuery, StandaloneMode};
+use crate::state::{State, Vesting, OWNER, STATE, VESTING};
 use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 
 pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> {
@@ -11,19 +11,52 @@ pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> Std
         incremental_donation: msg.incremental_donation,
         collective_ratio: msg.collective_ratio,
         manager: info.sender,
+        detached: false,
+        standalone_mode: StandaloneMode::default(),
     };
     STATE.save(deps.storage, &state)?;
 
+    if let Some(schedule) = msg.vesting {
+        schedule.validate()?;
+        let vesting = Vesting {
+            schedule,
+            tranches: vec![],
+            released: vec![],
+            withdrawn: vec![],
+        };
+        VESTING.save(deps.storage, &vesting)?;
+    }
+
     Ok(Response::new())
 }
 
 pub mod exec {
-    use cosmwasm_std::{to_binary, BankMsg, StdError, WasmMsg};
+    use cosmwasm_std::{to_binary, BankMsg, Coin, Decimal, StdError, WasmMsg};
+
+    use crate::coins::{add_coins, sub_coins_saturating};
+    use crate::msg::Profile;
+    use crate::state::PROFILE;
 
     use super::*;
 
-    pub fn donate(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    pub fn donate(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
         let mut state = STATE.load(deps.storage)?;
+
+        if state.detached {
+            if state.standalone_mode == StandaloneMode::Reject {
+                return Err(StdError::generic_err(
+                    "Peer owner is no longer a pool member",
+                ));
+            }
+        } else {
+            let manager: ManagerPausedResp = deps
+                .querier
+                .query_wasm_smart(&state.manager, &ManagerQuery::Paused {})?;
+            if manager.paused {
+                return Err(StdError::generic_err("Donations are paused"));
+            }
+        }
+
         let increment = info.funds.iter().any(|coin| {
             coin.denom == state.incremental_donation.denom
                 && coin.amount >= state.incremental_donation.amount
@@ -34,24 +67,38 @@ pub mod exec {
             STATE.save(deps.storage, &state)?;
         }
 
-        let collective_donation: Vec<_> = info
-            .funds
-            .into_iter()
-            .map(|mut coin| {
-                coin.amount = coin.amount * state.collective_ratio;
-                coin
-            })
-            .collect();
-
-        let donate_msg = ManagerExec::Donate {};
-        let donate_msg = WasmMsg::Execute {
-            contract_addr: state.manager.to_string(),
-            msg: to_binary(&donate_msg)?,
-            funds: collective_donation,
+        let collective_donation: Vec<_> = if state.detached {
+            vec![]
+        } else {
+            info.funds
+                .into_iter()
+                .map(|mut coin| {
+                    coin.amount = coin.amount * state.collective_ratio;
+                    coin
+                })
+                .collect()
         };
 
-        let resp = Response::new()
-            .add_message(donate_msg)
+        if let Some(mut vesting) = VESTING.may_load(deps.storage)? {
+            // The collective part is still on the balance, but it is about to leave
+            let balance = deps.querier.query_all_balances(&env.contract.address)?;
+            let balance = sub_coins_saturating(&balance, &collective_donation);
+            vesting.checkpoint(&balance, env.block.time);
+            VESTING.save(deps.storage, &vesting)?;
+        }
+
+        let mut resp = Response::new();
+        if !state.detached {
+            let donate_msg = ManagerExec::Donate {};
+            let donate_msg = WasmMsg::Execute {
+                contract_addr: state.manager.to_string(),
+                msg: to_binary(&donate_msg)?,
+                funds: collective_donation,
+            };
+            resp = resp.add_message(donate_msg);
+        }
+
+        let resp = resp
             .add_attribute("action", "donate")
             .add_attribute("sender", info.sender)
             .add_attribute("donators_increment", if increment { "yes" } else { "no" });
@@ -59,30 +106,207 @@ pub mod exec {
         Ok(resp)
     }
 
-    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+    pub fn withdraw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Option<Vec<Coin>>,
+        recipient: Option<String>,
+    ) -> StdResult<Response> {
         let owner = OWNER.load(deps.storage)?;
         if info.sender != owner {
             return Err(StdError::generic_err("Unauthorized"));
         }
 
-        let donations = deps.querier.query_all_balances(env.contract.address)?;
+        let recipient = recipient
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?
+            .unwrap_or(owner);
+
+        let balance = deps.querier.query_all_balances(&env.contract.address)?;
+        let mut vesting = VESTING.may_load(deps.storage)?;
+        let available = match &mut vesting {
+            Some(vesting) => {
+                vesting.checkpoint(&balance, env.block.time);
+                vesting.withdrawable(env.block.time)
+            }
+            None => balance,
+        };
+
+        let donations = match amount {
+            Some(amount) => {
+                validate_withdrawal(&amount, &available)?;
+                amount
+            }
+            None if available.is_empty() => {
+                return Err(StdError::generic_err("Nothing to withdraw"))
+            }
+            None => available,
+        };
+
+        if let Some(mut vesting) = vesting {
+            add_coins(&mut vesting.withdrawn, &donations);
+            VESTING.save(deps.storage, &vesting)?;
+        }
 
         let resp = Response::new()
             .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
+                to_address: recipient.to_string(),
                 amount: donations,
             })
             .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient);
+
+        Ok(resp)
+    }
+
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        incremental_donation: Option<Coin>,
+        collective_ratio: Option<Decimal>,
+    ) -> StdResult<Response> {
+        let mut state = STATE.load(deps.storage)?;
+        if info.sender != state.manager {
+            return Err(StdError::generic_err("Unauthorized"));
+        }
+
+        if let Some(incremental_donation) = incremental_donation {
+            state.incremental_donation = incremental_donation;
+        }
+
+        if let Some(collective_ratio) = collective_ratio {
+            state.collective_ratio = collective_ratio;
+        }
+
+        STATE.save(deps.storage, &state)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "update_config")
             .add_attribute("sender", info.sender);
 
         Ok(resp)
     }
+
+    pub fn detach(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+        let mut state = STATE.load(deps.storage)?;
+        if info.sender != state.manager {
+            return Err(StdError::generic_err("Unauthorized"));
+        }
+
+        state.detached = true;
+        STATE.save(deps.storage, &state)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "detach")
+            .add_attribute("sender", info.sender);
+
+        Ok(resp)
+    }
+
+    pub fn set_standalone_mode(
+        deps: DepsMut,
+        info: MessageInfo,
+        mode: StandaloneMode,
+    ) -> StdResult<Response> {
+        let owner = OWNER.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(StdError::generic_err("Unauthorized"));
+        }
+
+        STATE.update(deps.storage, |mut state| -> StdResult<_> {
+            state.standalone_mode = mode;
+            Ok(state)
+        })?;
+
+        let resp = Response::new()
+            .add_attribute("action", "set_standalone_mode")
+            .add_attribute("sender", info.sender);
+
+        Ok(resp)
+    }
+
+    pub fn set_profile(deps: DepsMut, info: MessageInfo, profile: Profile) -> StdResult<Response> {
+        let owner = OWNER.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(StdError::generic_err("Unauthorized"));
+        }
+
+        profile.validate()?;
+        PROFILE.save(deps.storage, &profile)?;
+
+        let mut resp = Response::new();
+        let state = STATE.load(deps.storage)?;
+        if !state.detached {
+            let update_msg = ManagerExec::UpdateProfile {
+                name: profile.name,
+                tags: profile.tags,
+            };
+            let update_msg = WasmMsg::Execute {
+                contract_addr: state.manager.to_string(),
+                msg: to_binary(&update_msg)?,
+                funds: vec![],
+            };
+            resp = resp.add_message(update_msg);
+        }
+
+        let resp = resp
+            .add_attribute("action", "set_profile")
+            .add_attribute("sender", info.sender);
+
+        Ok(resp)
+    }
+
+    /// Verifies that every requested coin is non-zero, requested at most once, and covered by
+    /// the available funds.
+    fn validate_withdrawal(amount: &[Coin], available: &[Coin]) -> StdResult<()> {
+        if amount.is_empty() {
+            return Err(StdError::generic_err("Nothing to withdraw"));
+        }
+
+        for (idx, coin) in amount.iter().enumerate() {
+            if coin.amount.is_zero() {
+                return Err(StdError::generic_err(format!(
+                    "Cannot withdraw zero {}",
+                    coin.denom
+                )));
+            }
+
+            if amount[..idx].iter().any(|c| c.denom == coin.denom) {
+                return Err(StdError::generic_err(format!(
+                    "Duplicated denom {} in withdrawal",
+                    coin.denom
+                )));
+            }
+
+            let funds = available
+                .iter()
+                .find(|c| c.denom == coin.denom)
+                .map(|c| c.amount)
+                .unwrap_or_default();
+
+            if coin.amount > funds {
+                return Err(StdError::generic_err(format!(
+                    "Insufficient funds: requested {}, available {}{}",
+                    coin, funds, coin.denom
+                )));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub mod query {
     use super::*;
 
-    use crate::msg::{ConfigResp, DonatorsResp, ManagerResp, OwnerResp, PendingDonationsResp};
+    use crate::coins::sub_coins_saturating;
+    use crate::msg::{
+        ConfigResp, DonatorsResp, ManagerResp, MembershipResp, OwnerResp, PendingDonationsResp,
+        ProfileResp, VestingResp,
+    };
+    use crate::state::PROFILE;
 
     pub fn owner(deps: Deps) -> StdResult<OwnerResp> {
         let owner = OWNER.load(deps.storage)?;
@@ -124,4 +348,46 @@ pub mod query {
             donators: state.donators,
         })
     }
+
+    pub fn vesting(deps: Deps, env: Env) -> StdResult<VestingResp> {
+        let balance = deps.querier.query_all_balances(&env.contract.address)?;
+
+        let mut vesting = match VESTING.may_load(deps.storage)? {
+            Some(vesting) => vesting,
+            None => {
+                return Ok(VestingResp {
+                    schedule: None,
+                    locked: vec![],
+                    vested: balance.clone(),
+                    withdrawn: vec![],
+                    withdrawable: balance,
+                })
+            }
+        };
+
+        vesting.checkpoint(&balance, env.block.time);
+        let vested = vesting.vested(env.block.time);
+
+        Ok(VestingResp {
+            locked: sub_coins_saturating(&vesting.received(&balance), &vested),
+            withdrawable: sub_coins_saturating(&vested, &vesting.withdrawn),
+            vested,
+            withdrawn: vesting.withdrawn,
+            schedule: Some(vesting.schedule),
+        })
+    }
+
+    pub fn membership(deps: Deps) -> StdResult<MembershipResp> {
+        let state = STATE.load(deps.storage)?;
+        Ok(MembershipResp {
+            manager: state.manager,
+            member: !state.detached,
+            standalone_mode: state.standalone_mode,
+        })
+    }
+
+    pub fn profile(deps: Deps) -> StdResult<ProfileResp> {
+        let profile = PROFILE.may_load(deps.storage)?;
+        Ok(ProfileResp { profile })
+    }
 }
@@ -1,10 +1,12 @@
+pub mod coins;
 pub mod contract;
 pub mod msg;
 pub mod state;
+mod vesting;
 
-use cosmwasm_std::{
-    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -27,11 +29,23 @@ pub fn execute(
     use msg::ExecMsg::*;
 
     match msg {
-        Donate {} => exec::donate(deps, info),
-        Withdraw {} => exec::withdraw(deps, env, info),
+        Donate {} => exec::donate(deps, env, info),
+        Withdraw { amount, recipient } => exec::withdraw(deps, env, info, amount, recipient),
+        UpdateConfig {
+            incremental_donation,
+            collective_ratio,
+        } => exec::update_config(deps, info, incremental_donation, collective_ratio),
+        Detach {} => exec::detach(deps, info),
+        SetStandaloneMode { mode } => exec::set_standalone_mode(deps, info, mode),
+        SetProfile { profile } => exec::set_profile(deps, info, profile),
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: msg::MigrateMsg) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use contract::query;
@@ -43,6 +57,9 @@ pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
         PendingDonations { denom } => to_binary(&query::pending_donations(deps, env, denom)?),
         Config {} => to_binary(&query::config(deps)?),
         Donators {} => to_binary(&query::donators(deps)?),
+        Vesting {} => to_binary(&query::vesting(deps, env)?),
+        Membership {} => to_binary(&query::membership(deps)?),
+        Profile {} => to_binary(&query::profile(deps)?),
     }
 }
 
@@ -72,6 +89,7 @@ mod tests {
                     owner: "owner".to_string(),
                     incremental_donation: coin(100, "utgd"),
                     collective_ratio: Decimal::percent(60),
+                    vesting: None,
                 },
                 &[],
                 "Contract",
@@ -123,6 +141,7 @@ mod tests {
                     owner: "owner".to_string(),
                     incremental_donation: coin(100, "utgd"),
                     collective_ratio: Decimal::percent(60),
+                    vesting: None,
                 },
                 &[],
                 "Contract",
@@ -146,7 +165,10 @@ mod tests {
         app.execute_contract(
             Addr::unchecked("owner"),
             addr.clone(),
-            &ExecMsg::Withdraw {},
+            &ExecMsg::Withdraw {
+                amount: None,
+                recipient: None,
+            },
             &[],
         )
         .unwrap();
@@ -168,4 +190,72 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn partial_withdraw() {
+        let mut app = App::default();
+        let code_id = app.store_code(contract());
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("manager"),
+                &msg::InstantiateMsg {
+                    owner: "owner".to_string(),
+                    incremental_donation: coin(100, "utgd"),
+                    collective_ratio: Decimal::percent(60),
+                    vesting: None,
+                },
+                &[],
+                "Contract",
+                None,
+            )
+            .unwrap();
+
+        app.init_modules(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &addr, vec![coin(500, "utgd"), coin(300, "uatom")])
+                .unwrap();
+        });
+
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &ExecMsg::Withdraw {
+                amount: Some(coins(600, "utgd")),
+                recipient: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &ExecMsg::Withdraw {
+                amount: Some(coins(200, "utgd")),
+                recipient: Some("exchange".to_owned()),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let donations: PendingDonationsResp = app
+            .wrap()
+            .query_wasm_smart(addr.clone(), &QueryMsg::PendingDonations { denom: None })
+            .unwrap();
+        assert_eq!(
+            donations.donations,
+            vec![coin(300, "uatom"), coin(300, "utgd")]
+        );
+
+        assert_eq!(
+            coin(200, "utgd"),
+            app.wrap().query_balance("exchange", "utgd").unwrap()
+        );
+        assert_eq!(
+            coin(0, "utgd"),
+            app.wrap().query_balance("owner", "utgd").unwrap()
+        );
+    }
 }
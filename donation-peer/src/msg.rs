@@ -1,10 +1,24 @@
-use cosmwasm_std::{Addr, Coin, Decimal};
+use cosmwasm_std::{Addr, Coin, Decimal, StdError, StdResult};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ManagerExec {
     Donate {},
+    UpdateProfile { name: String, tags: Vec<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ManagerQuery {
+    Paused {},
+}
+
+/// Part of the manager `Paused {}` query response used by the peer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ManagerPausedResp {
+    pub paused: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -13,15 +27,162 @@ pub struct InstantiateMsg {
     pub owner: String,
     pub incremental_donation: Coin,
     pub collective_ratio: Decimal,
+    #[serde(default)]
+    pub vesting: Option<VestingSchedule>,
+}
+
+/// Release schedule of the funds received by the peer. Every received amount is locked for
+/// `cliff` seconds, and then released linearly so it is fully available `duration` seconds after
+/// it was received. Setting `cliff` equal to `duration` releases everything at once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingSchedule {
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) -> StdResult<()> {
+        if self.duration == 0 {
+            return Err(StdError::generic_err("Vesting duration must be non-zero"));
+        }
+
+        if self.cliff > self.duration {
+            return Err(StdError::generic_err(
+                "Vesting cliff cannot exceed vesting duration",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+pub const MAX_PROFILE_NAME_LEN: usize = 64;
+pub const MAX_PROFILE_DESCRIPTION_LEN: usize = 1024;
+pub const MAX_PROFILE_URL_LEN: usize = 256;
+pub const MAX_PROFILE_LINKS: usize = 5;
+pub const MAX_PROFILE_TAGS: usize = 10;
+pub const MAX_PROFILE_TAG_LEN: usize = 32;
+
+/// Public profile of the peer owner. Name and tags are mirrored into the manager.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub links: Vec<String>,
+    /// Lowercase letters, digits and dashes only
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Profile {
+    pub fn validate(&self) -> StdResult<()> {
+        if self.name.is_empty() || self.name.len() > MAX_PROFILE_NAME_LEN {
+            return Err(StdError::generic_err(format!(
+                "Profile name must be between 1 and {} bytes",
+                MAX_PROFILE_NAME_LEN
+            )));
+        }
+
+        if self.description.len() > MAX_PROFILE_DESCRIPTION_LEN {
+            return Err(StdError::generic_err(format!(
+                "Profile description cannot exceed {} bytes",
+                MAX_PROFILE_DESCRIPTION_LEN
+            )));
+        }
+
+        if self.links.len() > MAX_PROFILE_LINKS {
+            return Err(StdError::generic_err(format!(
+                "Profile cannot have more than {} links",
+                MAX_PROFILE_LINKS
+            )));
+        }
+
+        let urls = self.avatar_url.iter().chain(&self.links);
+        if urls.into_iter().any(|url| url.len() > MAX_PROFILE_URL_LEN) {
+            return Err(StdError::generic_err(format!(
+                "Profile URLs cannot exceed {} bytes",
+                MAX_PROFILE_URL_LEN
+            )));
+        }
+
+        if self.tags.len() > MAX_PROFILE_TAGS {
+            return Err(StdError::generic_err(format!(
+                "Profile cannot have more than {} tags",
+                MAX_PROFILE_TAGS
+            )));
+        }
+
+        for (idx, tag) in self.tags.iter().enumerate() {
+            let valid = !tag.is_empty()
+                && tag.len() <= MAX_PROFILE_TAG_LEN
+                && tag
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+            if !valid {
+                return Err(StdError::generic_err(format!(
+                    "Invalid profile tag: {}",
+                    tag
+                )));
+            }
+
+            if self.tags[..idx].contains(tag) {
+                return Err(StdError::generic_err(format!(
+                    "Duplicated profile tag: {}",
+                    tag
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How the peer handles donations after its owner left the pool
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StandaloneMode {
+    /// Donations are accepted, and the whole amount stays on the peer
+    #[default]
+    KeepAll,
+    /// Donations are rejected
+    Reject,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecMsg {
     Donate {},
-    Withdraw {},
+    Withdraw {
+        amount: Option<Vec<Coin>>,
+        recipient: Option<String>,
+    },
+    /// Manager only - propagates changes of the pool configuration
+    UpdateConfig {
+        incremental_donation: Option<Coin>,
+        collective_ratio: Option<Decimal>,
+    },
+    /// Manager only - the owner left the pool, peer switches to the standalone mode
+    Detach {},
+    /// Owner only - sets how donations are handled once the peer is detached
+    SetStandaloneMode {
+        mode: StandaloneMode,
+    },
+    /// Owner only - sets the peer profile, mirroring its name and tags into the manager
+    SetProfile {
+        profile: Profile,
+    },
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -30,6 +191,9 @@ pub enum QueryMsg {
     PendingDonations { denom: Option<String> },
     Config {},
     Donators {},
+    Vesting {},
+    Membership {},
+    Profile {},
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -62,3 +226,28 @@ pub struct ConfigResp {
 pub struct DonatorsResp {
     pub donators: u64,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingResp {
+    pub schedule: Option<VestingSchedule>,
+    pub locked: Vec<Coin>,
+    pub vested: Vec<Coin>,
+    pub withdrawn: Vec<Coin>,
+    pub withdrawable: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MembershipResp {
+    pub manager: Addr,
+    pub member: bool,
+    pub standalone_mode: StandaloneMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ProfileResp {
+    /// Not set until the owner sets it
+    pub profile: Option<Profile>,
+}
@@ -1,7 +1,9 @@
-use cosmwasm_std::{Addr, Coin, Decimal};
+use cosmwasm_std::{Addr, Coin, Decimal, Timestamp};
 use cw_storage_plus::Item;
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{Profile, StandaloneMode, VestingSchedule};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct State {
@@ -9,7 +11,38 @@ pub struct State {
     pub incremental_donation: Coin,
     pub collective_ratio: Decimal,
     pub manager: Addr,
+    // Set when the owner left the pool - the peer no longer forwards anything to the manager
+    #[serde(default)]
+    pub detached: bool,
+    #[serde(default)]
+    pub standalone_mode: StandaloneMode,
 }
 
 pub const STATE: Item<State> = Item::new("state");
 pub const OWNER: Item<Addr> = Item::new("owner");
+pub const PROFILE: Item<Profile> = Item::new("profile");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Tranche {
+    pub received_at: Timestamp,
+    pub amount: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Vesting {
+    pub schedule: VestingSchedule,
+    // Funds which are still being released, in order of observation
+    pub tranches: Vec<Tranche>,
+    // Funds from tranches which are fully released
+    pub released: Vec<Coin>,
+    pub withdrawn: Vec<Coin>,
+}
+
+// Tranches received within `duration / MAX_TRANCHES` from the latest one are merged into it,
+// so at most this many tranches are being released at once
+pub const MAX_TRANCHES: u64 = 32;
+
+// Present only if the peer was created with a vesting schedule
+pub const VESTING: Item<Vesting> = Item::new("vesting");
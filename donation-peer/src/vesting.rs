@@ -0,0 +1,80 @@
+use cosmwasm_std::{Coin, Timestamp};
+
+use crate::coins::{add_coins, sub_coins_saturating};
+use crate::state::{Tranche, Vesting, MAX_TRANCHES};
+
+impl Vesting {
+    /// Everything the peer ever received - funds it holds plus funds already withdrawn
+    pub fn received(&self, balance: &[Coin]) -> Vec<Coin> {
+        let mut received = balance.to_vec();
+        add_coins(&mut received, &self.withdrawn);
+        received
+    }
+
+    /// Records funds not seen before as a new tranche received at `now`, and folds tranches
+    /// which are fully released. Funds received shortly after the latest tranche are merged
+    /// into it, and are released as if they were received with it.
+    ///
+    /// Funds sent by the manager do not trigger any peer execution, so they are recorded the
+    /// first time the peer looks at its balance after they arrived.
+    pub fn checkpoint(&mut self, balance: &[Coin], now: Timestamp) {
+        let mut recorded = self.released.clone();
+        for tranche in &self.tranches {
+            add_coins(&mut recorded, &tranche.amount);
+        }
+
+        let duration = self.schedule.duration;
+        let window = (duration / MAX_TRANCHES).max(1);
+
+        let new = sub_coins_saturating(&self.received(balance), &recorded);
+        match self.tranches.last_mut() {
+            _ if new.is_empty() => (),
+            Some(last) if now < last.received_at.plus_seconds(window) => {
+                add_coins(&mut last.amount, &new);
+            }
+            _ => self.tranches.push(Tranche {
+                received_at: now,
+                amount: new,
+            }),
+        }
+
+        let (released, tranches): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tranches)
+            .into_iter()
+            .partition(|tranche| tranche.received_at.plus_seconds(duration) <= now);
+
+        for tranche in released {
+            add_coins(&mut self.released, &tranche.amount);
+        }
+        self.tranches = tranches;
+    }
+
+    /// All funds released until `now`, including already withdrawn ones
+    pub fn vested(&self, now: Timestamp) -> Vec<Coin> {
+        let mut vested = self.released.clone();
+
+        for tranche in &self.tranches {
+            let elapsed = now.seconds().saturating_sub(tranche.received_at.seconds());
+            if elapsed < self.schedule.cliff {
+                continue;
+            }
+
+            let elapsed = elapsed.min(self.schedule.duration);
+            let amount: Vec<_> = tranche
+                .amount
+                .iter()
+                .map(|coin| Coin {
+                    denom: coin.denom.clone(),
+                    amount: coin.amount.multiply_ratio(elapsed, self.schedule.duration),
+                })
+                .collect();
+            add_coins(&mut vested, &amount);
+        }
+
+        vested
+    }
+
+    /// Released funds which were not withdrawn yet
+    pub fn withdrawable(&self, now: Timestamp) -> Vec<Coin> {
+        sub_coins_saturating(&self.vested(now), &self.withdrawn)
+    }
+}